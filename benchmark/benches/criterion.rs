@@ -1,55 +1,174 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use std::{
+    error::Error,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-macro_rules! doit {
-    ($($day:ident),+$(,)?) => {
-        pub fn aoc_benchmark(c: &mut Criterion) {
-            $(c.bench_function(stringify!($day), |b| b.iter($day::solve));)+
-            c.bench_function("all", |b| b.iter(|| ($($day::solve()),+)));
+use aoc_benchmark::{estimates, Phase, DAYS};
+use criterion::{criterion_group, BenchmarkId, Criterion};
+#[cfg(target_os = "linux")]
+use pprof::criterion::{Output, PProfProfiler};
+use serde::Serialize;
+
+pub fn aoc_benchmark(c: &mut Criterion) {
+    for day in DAYS {
+        c.bench_function(day.name, |b| b.iter(day.solve));
+    }
+    c.bench_function("all", |b| b.iter(|| DAYS.iter().for_each(|day| (day.solve)())));
+}
+
+pub fn phases(c: &mut Criterion) {
+    for phase in Phase::ALL {
+        let mut group = c.benchmark_group(phase.name());
+        for day in DAYS {
+            (day.bench_phase)(phase, &mut |routine| {
+                group.bench_function(day.name, |b| b.iter(&mut *routine));
+            });
+        }
+        group.finish();
+    }
+}
+
+/// The inputs in `dir`, by file name, or none if there is no such directory.
+fn read_inputs(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut inputs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            inputs.push((name, std::fs::read_to_string(&path)?));
         }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
 
-        criterion_group! {
-            name = benches;
+/// Benchmark each day against every input in `$AOC_INPUTS/dayNN`, so that an optimization that only suits the embedded
+/// input shows up as such. Criterion reports each input on its own, then the median over all of them is printed.
+pub fn inputs(c: &mut Criterion) {
+    let Some(inputs_dir) = std::env::var_os("AOC_INPUTS").map(PathBuf::from) else {
+        return;
+    };
 
-            config = Criterion::default()
-                // .significance_level(0.1)
-                // .sample_size(500)
-                // .measurement_time(Duration::from_secs(15))
-                // .warm_up_time(Duration::from_secs(5))
-                // .noise_threshold(0.05)
-            ;
+    for day in DAYS {
+        let inputs = match read_inputs(&inputs_dir.join(day.name)) {
+            Ok(inputs) => inputs,
+            Err(err) => {
+                eprintln!("skipping {}: could not read its inputs: {err}", day.name);
+                continue;
+            }
+        };
+        if inputs.is_empty() {
+            continue;
+        }
 
-            targets = aoc_benchmark
+        let group_name = format!("{}/inputs", day.name);
+        let started = SystemTime::now();
+        let mut group = c.benchmark_group(&group_name);
+        for (name, input) in &inputs {
+            if let Err(err) = (day.solve_input)(input) {
+                eprintln!("skipping {group_name}/{name}: {err}");
+                continue;
+            }
+            group.bench_with_input(BenchmarkId::from_parameter(name), input.as_str(), |b, input| {
+                b.iter(|| (day.solve_input)(input))
+            });
         }
+        group.finish();
 
-        criterion_main!(benches);
+        // Only count the inputs that were benchmarked just now, not ones left over from an earlier run.
+        let group_dir = estimates::criterion_home().join(estimates::directory_name(&group_name));
+        let timings = inputs
+            .iter()
+            .filter_map(|(name, _)| estimates::typical_since(&group_dir.join(estimates::directory_name(name)), started))
+            .collect::<Vec<_>>();
+        let count = timings.len();
+        if let Some(median) = estimates::median(timings) {
+            println!("{group_name:<24}median: {median:.2?} over {count} inputs\n");
+        }
+    }
+}
+
+/// Criterion's settings for every benchmark. On Linux, running with `--profile-time SECONDS` profiles each benchmark
+/// instead of measuring it, writing a flamegraph to `target/criterion/<benchmark>/profile/flamegraph.svg`.
+fn config() -> Criterion {
+    let config = Criterion::default();
+    #[cfg(target_os = "linux")]
+    let config = config.with_profiler(PProfProfiler::new(1000, Output::Flamegraph(None)));
+    config
+}
+
+/// One day's timings from this run, in nanoseconds, or `None` for the benchmarks that were filtered out.
+#[derive(Serialize)]
+struct Record {
+    day: &'static str,
+    parse_ns: Option<u64>,
+    part1_ns: Option<u64>,
+    part2_ns: Option<u64>,
+    solve_ns: Option<u64>,
+    git_commit: &'static str,
+}
+
+/// Write what this run measured to `$AOC_BENCH_EXPORT`, as JSON or CSV depending on its extension, so it can be tracked
+/// over time like the runner's `--format` output.
+fn export(started: SystemTime) -> Result<(), Box<dyn Error>> {
+    let Some(path) = std::env::var_os("AOC_BENCH_EXPORT").map(PathBuf::from) else {
+        return Ok(());
     };
+
+    let home = estimates::criterion_home();
+    let nanos = |dir: PathBuf| estimates::typical_since(&dir, started).map(|timing| timing.as_nanos() as u64);
+    let records = DAYS
+        .iter()
+        .map(|day| Record {
+            day: day.name,
+            parse_ns: nanos(home.join(Phase::Parse.name()).join(day.name)),
+            part1_ns: nanos(home.join(Phase::Part1.name()).join(day.name)),
+            part2_ns: nanos(home.join(Phase::Part2.name()).join(day.name)),
+            solve_ns: nanos(home.join(day.name)),
+            git_commit: env!("AOC_GIT_COMMIT"),
+        })
+        .filter(|record| {
+            [record.parse_ns, record.part1_ns, record.part2_ns, record.solve_ns]
+                .iter()
+                .any(Option::is_some)
+        })
+        .collect::<Vec<_>>();
+
+    let file = File::create(&path)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::to_writer_pretty(file, &records)?,
+        Some("csv") => {
+            let mut writer = csv::Writer::from_writer(file);
+            for record in &records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+        _ => return Err("AOC_BENCH_EXPORT should end in .json or .csv".into()),
+    }
+    Ok(())
 }
 
-#[rustfmt::skip]
-doit!(
-    day01,
-    day02,
-    day03,
-    day04,
-    day05,
-    day06,
-    day07,
-    day08,
-    day09,
-    day10,
-    day11,
-    day12,
-    day13,
-    day14,
-    day15,
-    day16,
-    day17,
-    day18,
-    day19,
-    day20,
-    day21,
-    day22,
-    day23,
-    day24,
-    day25,
-);
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = aoc_benchmark, phases, inputs
+}
+
+fn main() {
+    let started = SystemTime::now();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    if let Err(err) = export(started) {
+        eprintln!("could not export the results: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -1,34 +1,13 @@
-macro_rules! doit {
-    ($($day:ident: $solve:ident),+$(,)?) => {
-        $(use $day::solve as $solve;)+
-        iai::main!($($solve),+);
-    };
-}
+use aoc_benchmark::{Phase, DAYS};
 
-doit!(
-    day01: day01_solve,
-    day02: day02_solve,
-    day03: day03_solve,
-    day04: day04_solve,
-    day05: day05_solve,
-    day06: day06_solve,
-    day07: day07_solve,
-    day08: day08_solve,
-    day09: day09_solve,
-    day10: day10_solve,
-    day11: day11_solve,
-    day12: day12_solve,
-    day13: day13_solve,
-    day14: day14_solve,
-    day15: day15_solve,
-    day16: day16_solve,
-    day17: day17_solve,
-    day18: day18_solve,
-    day19: day19_solve,
-    day20: day20_solve,
-    day21: day21_solve,
-    day22: day22_solve,
-    day23: day23_solve,
-    day24: day24_solve,
-    day25: day25_solve,
-);
+fn main() {
+    let mut benchmarks = DAYS.iter().map(|day| (day.name, day.solve)).collect::<Vec<_>>();
+    for day in DAYS {
+        for (phase, run) in Phase::ALL.iter().zip(day.phases) {
+            // iai names its output files after the benchmarks, so these can't have a `/` in them like criterion's.
+            let name: &'static str = Box::leak(format!("{}_{}", day.name, phase.name()).into_boxed_str());
+            benchmarks.push((name, run));
+        }
+    }
+    iai::runner(&benchmarks.iter().collect::<Vec<_>>());
+}
@@ -0,0 +1,21 @@
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Record which commit the runner was built from, for the results it exports.
+fn main() {
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+    let commit = match (git(&["rev-parse", "HEAD"]), git(&["status", "--porcelain"])) {
+        (Some(commit), Some(status)) if !status.is_empty() => format!("{commit}-dirty"),
+        (Some(commit), _) => commit,
+        (None, _) => "unknown".to_owned(),
+    };
+    println!("cargo:rustc-env=AOC_GIT_COMMIT={commit}");
+}
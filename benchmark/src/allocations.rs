@@ -0,0 +1,103 @@
+//! A global allocator that keeps count of what goes through it, to see how much each day allocates.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Relaxed);
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn allocated(size: usize) {
+    ALLOCATIONS.fetch_add(1, Relaxed);
+    BYTES.fetch_add(size, Relaxed);
+    let current = CURRENT.fetch_add(size, Relaxed) + size;
+    PEAK.fetch_max(current, Relaxed);
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// What was allocated while running something.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Allocations {
+    /// How many times memory was allocated, counting each reallocation as a new allocation.
+    pub count: usize,
+    /// The total size of all those allocations.
+    pub bytes: usize,
+    /// The most memory that was in use at once, on top of what already was beforehand.
+    pub peak: usize,
+}
+
+/// Run `f`, counting what it allocates. Other threads' allocations get counted too, so this shouldn't run alongside
+/// anything else.
+pub fn count<T>(f: impl FnOnce() -> T) -> (T, Allocations) {
+    let count = ALLOCATIONS.load(Relaxed);
+    let bytes = BYTES.load(Relaxed);
+    let current = CURRENT.load(Relaxed);
+    PEAK.store(current, Relaxed);
+    let result = f();
+    let allocations = Allocations {
+        count: ALLOCATIONS.load(Relaxed) - count,
+        bytes: BYTES.load(Relaxed) - bytes,
+        peak: PEAK.load(Relaxed).saturating_sub(current),
+    };
+    (result, allocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+
+    use super::*;
+
+    #[test]
+    fn counts_allocations() {
+        let (_, allocations) = count(|| {
+            let mut v = black_box(Vec::<u8>::with_capacity(100));
+            v.extend([0; 100]);
+            drop(black_box(vec![0u8; 50]));
+            v
+        });
+        // The test harness's other threads may allocate at the same time, so there can be more.
+        assert!(allocations.count >= 2, "{:?}", allocations);
+        assert!(allocations.bytes >= 150, "{:?}", allocations);
+        assert!(allocations.peak >= 150, "{:?}", allocations);
+    }
+}
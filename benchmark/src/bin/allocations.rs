@@ -0,0 +1,42 @@
+use std::process::ExitCode;
+
+use aoc_benchmark::{allocations, find_day, Day, DAYS};
+
+const USAGE: &str = "usage: allocations [DAY...|all]";
+
+fn parse_args() -> Result<Vec<&'static Day>, String> {
+    let mut days = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            "all" => days.extend(DAYS),
+            day => days.push(find_day(day).ok_or_else(|| format!("unknown day {day:?}"))?),
+        }
+    }
+    if days.is_empty() {
+        days.extend(DAYS);
+    }
+    Ok(days)
+}
+
+fn main() -> ExitCode {
+    let days = match parse_args() {
+        Ok(days) => days,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("| day | allocations | bytes allocated | peak heap |");
+    println!("|---|---:|---:|---:|");
+    for day in days {
+        let ((), allocations) = allocations::count(day.solve);
+        println!(
+            "| {} | {} | {} | {} |",
+            day.name, allocations.count, allocations.bytes, allocations.peak
+        );
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,173 @@
+use std::{io::Read as _, process::ExitCode, time::Duration};
+
+use aoc_benchmark::{find_day, metadata::DayMetadata, Day, Part, Run, DAYS};
+use serde::Serialize;
+
+const USAGE: &str = "usage: aoc [DAY...|all] [--part 1|2] [--input PATH|-] [--format text|json|csv]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+struct Args {
+    days: Vec<&'static Day>,
+    part: Part,
+    input: Option<String>,
+    format: Format,
+}
+
+/// One part's answer, with everything needed to track performance over time.
+#[derive(Serialize)]
+struct Record {
+    day: &'static str,
+    part: u8,
+    answer: String,
+    parse_ns: u64,
+    solve_ns: u64,
+    /// The 64-bit FNV-1a hash of the input, to tell apart results for different inputs.
+    input_hash: String,
+    git_commit: &'static str,
+    start_time: Option<String>,
+    completion_time: Option<String>,
+}
+
+fn fnv1a(input: &str) -> u64 {
+    input.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut days = Vec::new();
+    let mut part = Part::Both;
+    let mut input_path = None;
+    let mut format = Format::Text;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--part" => {
+                part = match args.next().as_deref() {
+                    Some("1") => Part::One,
+                    Some("2") => Part::Two,
+                    other => return Err(format!("invalid part {other:?}")),
+                }
+            }
+            "-i" | "--input" => input_path = Some(args.next().ok_or("--input needs a path")?),
+            "-f" | "--format" => {
+                format = match args.next().as_deref() {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    Some("csv") => Format::Csv,
+                    other => return Err(format!("invalid format {other:?}")),
+                }
+            }
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            "all" => days.extend(DAYS),
+            day => days.push(find_day(day).ok_or_else(|| format!("unknown day {day:?}"))?),
+        }
+    }
+
+    if days.is_empty() {
+        days.extend(DAYS);
+    }
+
+    let input = match input_path.as_deref() {
+        None => None,
+        Some(_) if days.len() != 1 => return Err("--input needs exactly one day".to_owned()),
+        Some("-") => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("could not read stdin: {err}"))?;
+            Some(input)
+        }
+        Some(path) => Some(std::fs::read_to_string(path).map_err(|err| format!("could not read {path}: {err}"))?),
+    };
+
+    Ok(Args {
+        days,
+        part,
+        input,
+        format,
+    })
+}
+
+fn main() -> ExitCode {
+    let Args {
+        days,
+        part,
+        input,
+        format,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut total = Duration::ZERO;
+    let mut failed = false;
+    let mut records = Vec::new();
+    for day in days {
+        let day_input = input.as_deref().unwrap_or(day.input);
+        let Run { parse, part1, part2 } = match (day.run)(day_input, part) {
+            Ok(run) => run,
+            Err(err) => {
+                eprintln!("{} {err}", day.name);
+                failed = true;
+                continue;
+            }
+        };
+        let metadata = DayMetadata::of(day.name);
+        if format == Format::Text {
+            println!("{} parse took {parse:?}", day.name);
+        }
+        total += parse;
+        for (n, result) in [(1, part1), (2, part2)] {
+            if let Some((answer, elapsed)) = result {
+                if format == Format::Text {
+                    println!("{} part {n}: {answer} (took {elapsed:?})", day.name);
+                }
+                total += elapsed;
+                records.push(Record {
+                    day: day.name,
+                    part: n,
+                    answer,
+                    parse_ns: parse.as_nanos() as u64,
+                    solve_ns: elapsed.as_nanos() as u64,
+                    input_hash: format!("{:016x}", fnv1a(day_input)),
+                    git_commit: env!("AOC_GIT_COMMIT"),
+                    start_time: metadata.start_time.map(|time| time.to_string()),
+                    completion_time: metadata.completion_time.map(|time| time.to_string()),
+                });
+            }
+        }
+    }
+
+    match format {
+        Format::Text => println!("total: {total:?}"),
+        Format::Json => {
+            serde_json::to_writer_pretty(std::io::stdout().lock(), &records).expect("could not write results");
+            println!();
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+            for record in &records {
+                writer.serialize(record).expect("could not write results");
+            }
+            writer.flush().expect("could not write results");
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
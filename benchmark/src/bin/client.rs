@@ -0,0 +1,61 @@
+use std::{path::Path, process::ExitCode};
+
+use aoc_benchmark::{
+    client::{Client, Verdict},
+    metadata,
+};
+
+const USAGE: &str = "usage: client input DAY | client answer DAY PART ANSWER";
+
+/// Every answer submitted, kept next to the workspace manifest.
+const LOG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../submissions.json");
+
+/// Where wrong answers are also recorded, for each day to warn about.
+const BOUNDS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../answer_bounds.toml");
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    day.strip_prefix("day")
+        .unwrap_or(day)
+        .parse()
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+        .ok_or_else(|| format!("invalid day {day:?}"))
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    let session = std::env::var("SESSION_COOKIE").map_err(|_| "set SESSION_COOKIE to your session cookie")?;
+    let mut client = Client::new(metadata::year(), session, Path::new(LOG))
+        .map_err(|err| err.to_string())?
+        .with_answer_bounds(BOUNDS);
+    match args {
+        [command, day] if command == "input" => {
+            print!("{}", client.input(parse_day(day)?).map_err(|err| err.to_string())?);
+            Ok(true)
+        }
+        [command, day, part, answer] if command == "answer" => {
+            let part = match part.as_str() {
+                "1" => 1,
+                "2" => 2,
+                _ => return Err(format!("invalid part {part:?}")),
+            };
+            let verdict = client
+                .submit(parse_day(day)?, part, answer)
+                .map_err(|err| err.to_string())?;
+            println!("{answer} is {verdict}");
+            Ok(verdict == Verdict::Correct)
+        }
+        _ => Err(USAGE.to_owned()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
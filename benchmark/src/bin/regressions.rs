@@ -0,0 +1,96 @@
+use std::{process::ExitCode, time::Duration};
+
+use aoc_benchmark::{estimates, DAYS};
+
+const USAGE: &str = "usage: regressions [--baseline NAME] [--threshold PERCENT]";
+
+struct Args {
+    baseline: String,
+    /// How much slower than the baseline, in percent, a benchmark may get before it counts as a regression.
+    threshold: f64,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut baseline = "previous".to_owned();
+    let mut threshold = 5.0;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-b" | "--baseline" => baseline = args.next().ok_or("--baseline needs a name")?,
+            "-t" | "--threshold" => {
+                let value = args.next().ok_or("--threshold needs a percentage")?;
+                threshold = value
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|err| format!("invalid threshold {value:?}: {err}"))?;
+            }
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            other => return Err(format!("unexpected argument {other:?}")),
+        }
+    }
+
+    Ok(Args { baseline, threshold })
+}
+
+fn main() -> ExitCode {
+    let Args { baseline, threshold } = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let home = estimates::criterion_home();
+    let comparisons = DAYS
+        .iter()
+        .map(|day| day.name)
+        .chain(["all"])
+        .filter_map(|name| {
+            let dir = home.join(estimates::directory_name(name));
+            Some((
+                name,
+                estimates::typical(&dir, &baseline)?,
+                estimates::typical(&dir, "new")?,
+            ))
+        })
+        .collect::<Vec<_>>();
+    if comparisons.is_empty() {
+        eprintln!(
+            "no benchmarks under {} have both a {baseline:?} baseline and a latest run",
+            home.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    println!("| benchmark | {baseline} | latest | change |");
+    println!("|---|---:|---:|---:|");
+    let mut regressions = Vec::new();
+    for (name, before, after) in comparisons {
+        let change = (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0;
+        let change = if change > threshold {
+            regressions.push(name);
+            format!("**{change:+.1}%**")
+        } else {
+            format!("{change:+.1}%")
+        };
+        println!("| {name} | {} | {} | {change} |", show(before), show(after));
+    }
+
+    if regressions.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!(
+            "{} regressed by more than {threshold}%: {}",
+            regressions.len(),
+            regressions.join(", ")
+        );
+        ExitCode::FAILURE
+    }
+}
+
+fn show(duration: Duration) -> String {
+    format!("{duration:.2?}")
+}
@@ -0,0 +1,43 @@
+use std::{process::ExitCode, time::Duration};
+
+use aoc_benchmark::{metadata::DayMetadata, Part, Run, DAYS};
+
+/// Like `1h 02m 03s`, leaving out the hours when there are none.
+fn show_time_to_solve(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours == 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    }
+}
+
+fn main() -> ExitCode {
+    let mut failed = false;
+    println!("| day | time to solve | time to run |");
+    println!("|---|---:|---:|");
+    for day in DAYS {
+        let time_to_solve = DayMetadata::of(day.name)
+            .time_to_solve()
+            .map_or_else(|| "unknown".to_owned(), show_time_to_solve);
+        let time_to_run = match (day.run)(day.input, Part::Both) {
+            Ok(Run { parse, part1, part2 }) => {
+                let parts = part1.into_iter().chain(part2).map(|(_, elapsed)| elapsed);
+                format!("{:.2?}", parse + parts.sum::<Duration>())
+            }
+            Err(err) => {
+                eprintln!("{} {err}", day.name);
+                failed = true;
+                "failed".to_owned()
+            }
+        };
+        println!("| {} | {time_to_solve} | {time_to_run} |", day.name);
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,574 @@
+//! Fetching inputs from and submitting answers to the Advent of Code website. Every answer submitted is kept in a log,
+//! so that nothing already known to be wrong gets sent again, and nothing gets sent while the site has asked to wait.
+//! Wrong answers can also be added to `answer_bounds.toml`, so that the days themselves warn about them.
+
+use std::{
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+const USER_AGENT: &str = "PurpleMyst/aoc-template with much love! <3";
+
+/// What the site made of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying which way.
+    Wrong,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Verdict::Correct => "the right answer",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+            Verdict::Wrong => "wrong",
+        })
+    }
+}
+
+/// An answer that was submitted to one part of a day, and what came of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Submission {
+    /// Whether this shows that `answer` is wrong too: because it's the same, or past a bound this one set.
+    fn rules_out(&self, answer: &str) -> bool {
+        if self.answer == answer {
+            return self.verdict != Verdict::Correct;
+        }
+        let (Ok(bound), Ok(answer)) = (self.answer.parse::<i128>(), answer.parse::<i128>()) else {
+            return false;
+        };
+        match self.verdict {
+            Verdict::TooHigh => answer >= bound,
+            Verdict::TooLow => answer <= bound,
+            Verdict::Correct | Verdict::Wrong => false,
+        }
+    }
+}
+
+/// What gets kept between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Log {
+    /// When, in seconds since the Unix epoch, the site will take another answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait_until: Option<u64>,
+    #[serde(default)]
+    submissions: Vec<Submission>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Http(Box<ureq::Error>),
+    Io(io::Error),
+    Log(serde_json::Error),
+    /// An earlier submission shows the answer is wrong.
+    KnownWrong(Submission),
+    /// The part was already solved, with this other answer.
+    AlreadySolved(String),
+    /// The site won't take another answer for this long.
+    Throttled(Duration),
+    /// The site won't take answers to the part, either because it's solved already or because the one before isn't.
+    WrongLevel,
+    /// The site said something else, kept here in full.
+    Unrecognized(String),
+    /// The wrong answer couldn't be added to the registry of them.
+    Bounds(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Http(err) => write!(f, "could not reach the site: {err}"),
+            Error::Io(err) => write!(f, "could not read or write the submission log: {err}"),
+            Error::Log(err) => write!(f, "the submission log is malformed: {err}"),
+            Error::KnownWrong(earlier) => {
+                write!(
+                    f,
+                    "already known to be wrong, as {} was {}",
+                    earlier.answer, earlier.verdict
+                )
+            }
+            Error::AlreadySolved(answer) => write!(f, "already solved, with {answer}"),
+            Error::Throttled(wait) => write!(f, "the site needs another {}s before taking answers", wait.as_secs()),
+            Error::WrongLevel => f.write_str("the site isn't taking answers to that part"),
+            Error::Unrecognized(page) => write!(f, "could not make out what the site said: {}", snippet(page)),
+            Error::Bounds(err) => write!(f, "could not record the answer in answer_bounds.toml: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::Log(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        Self::Http(Box::new(err))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Log(err)
+    }
+}
+
+/// What the site said to an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Response {
+    /// What it thinks of the answer, and how long to wait before the next one, if at all.
+    Verdict(Verdict, Option<Duration>),
+    Throttled(Duration),
+    WrongLevel,
+    Unrecognized,
+}
+
+impl Response {
+    fn parse(page: &str) -> Self {
+        if page.contains("That's the right answer") {
+            Response::Verdict(Verdict::Correct, None)
+        } else if page.contains("That's not the right answer") {
+            let verdict = if page.contains("answer is too high") {
+                Verdict::TooHigh
+            } else if page.contains("answer is too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            };
+            Response::Verdict(verdict, minutes_to_wait(page))
+        } else if page.contains("You gave an answer too recently") {
+            Response::Throttled(time_left(page).unwrap_or(Duration::from_secs(60)))
+        } else if page.contains("You don't seem to be solving the right level") {
+            Response::WrongLevel
+        } else {
+            Response::Unrecognized
+        }
+    }
+}
+
+/// The start of what a page says, without the markup, to show with an error.
+fn snippet(page: &str) -> String {
+    const MAX_CHARS: usize = 100;
+    let article = page.split_once("<article>").map_or(page, |(_, article)| article);
+    let mut in_tag = false;
+    let text = article.chars().filter(|&c| {
+        let keep = !in_tag && c != '<';
+        in_tag = (in_tag || c == '<') && c != '>';
+        keep
+    });
+    let words = text.collect::<String>();
+    let mut text = words.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((cut, _)) = text.char_indices().nth(MAX_CHARS) {
+        text.truncate(cut);
+        text.push('…');
+    }
+    text
+}
+
+/// Read "please wait 5 minutes before trying again", which comes with wrong answers.
+fn minutes_to_wait(page: &str) -> Option<Duration> {
+    let (_, rest) = page.split_once("wait ")?;
+    let minutes = match rest.split_whitespace().next()? {
+        "one" => 1,
+        minutes => minutes.parse().ok()?,
+    };
+    Some(Duration::from_secs(60 * minutes))
+}
+
+/// Read "You have 1m 5s left to wait", which comes with answers given too soon.
+fn time_left(page: &str) -> Option<Duration> {
+    let (_, rest) = page.split_once("You have ")?;
+    let (left, _) = rest.split_once(" left")?;
+    left.split_whitespace().try_fold(Duration::ZERO, |total, amount| {
+        let seconds = if let Some(hours) = amount.strip_suffix('h') {
+            hours.parse::<u64>().ok()? * 3600
+        } else if let Some(minutes) = amount.strip_suffix('m') {
+            minutes.parse::<u64>().ok()? * 60
+        } else {
+            amount.strip_suffix('s')?.parse::<u64>().ok()?
+        };
+        Some(total + Duration::from_secs(seconds))
+    })
+}
+
+/// Add `submission` to `registry`, the contents of an `answer_bounds.toml`, under the list its verdict belongs in. The
+/// rest of the registry is kept as it was, comments and all.
+fn record_bound(registry: &str, submission: &Submission) -> Result<String, String> {
+    let key = match submission.verdict {
+        Verdict::TooHigh => "too_high",
+        Verdict::TooLow => "too_low",
+        Verdict::Wrong => "wrong",
+        Verdict::Correct => return Ok(registry.to_owned()),
+    };
+    let day = format!("day{:02}", submission.day);
+    let part = format!("part{}", submission.part);
+    let not_a = |what: &str, name: &str| format!("{name} should be a {what}");
+
+    let mut registry = registry
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|err| err.to_string())?;
+    let day_table = registry
+        .entry(&day)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| not_a("table", &day))?;
+    day_table.set_implicit(true);
+    let answers = day_table
+        .entry(&part)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| not_a("table", &format!("{day}.{part}")))?
+        .entry(key)
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or_else(|| not_a("list", &format!("{day}.{part}.{key}")))?;
+
+    let answer = submission.answer.as_str();
+    let known = answers.iter().any(|known| match known {
+        toml_edit::Value::Integer(known) => known.value().to_string() == answer,
+        toml_edit::Value::String(known) => known.value() == answer,
+        _ => false,
+    });
+    if !known {
+        match answer.parse::<i64>() {
+            Ok(answer) => answers.push(answer),
+            Err(_) => answers.push(answer),
+        }
+    }
+    Ok(registry.to_string())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    year: i64,
+    session: String,
+    log_path: PathBuf,
+    log: Log,
+    bounds_path: Option<PathBuf>,
+}
+
+impl Client {
+    /// A client for `year`'s puzzles, logged in with the `session` cookie, keeping its submissions at `log_path`.
+    pub fn new(year: i64, session: impl Into<String>, log_path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let log_path = log_path.into();
+        let log = match fs::read_to_string(&log_path) {
+            Ok(log) => serde_json::from_str(&log)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Log::default(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+            base_url: BASE_URL.to_owned(),
+            year,
+            session: session.into(),
+            log_path,
+            log,
+            bounds_path: None,
+        })
+    }
+
+    /// Talk to another server than the real site, like a local one for testing.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Also add every wrong answer to the registry at `bounds_path`, like the workspace's `answer_bounds.toml`.
+    pub fn with_answer_bounds(mut self, bounds_path: impl Into<PathBuf>) -> Self {
+        self.bounds_path = Some(bounds_path.into());
+        self
+    }
+
+    fn url(&self, day: u8, page: &str) -> String {
+        format!("{}/{}/day/{day}/{page}", self.base_url, self.year)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    pub fn input(&self, day: u8) -> Result<String, Error> {
+        let response = self
+            .agent
+            .get(&self.url(day, "input"))
+            .set("Cookie", &self.cookie())
+            .call()?;
+        Ok(response.into_string()?)
+    }
+
+    /// Every answer submitted to `part` of `day`, oldest first.
+    pub fn submissions(&self, day: u8, part: u8) -> impl Iterator<Item = &Submission> + '_ {
+        self.log
+            .submissions
+            .iter()
+            .filter(move |submission| submission.day == day && submission.part == part)
+    }
+
+    /// Submit `answer` to `part` of `day`, unless earlier submissions already show what the site will say, or it has
+    /// asked to wait.
+    ///
+    /// # Panics
+    ///
+    /// If `part` isn't 1 or 2.
+    pub fn submit(&mut self, day: u8, part: u8, answer: &str) -> Result<Verdict, Error> {
+        assert!(matches!(part, 1 | 2), "part should be 1 or 2, not {}", part);
+        let answer = answer.trim();
+        if let Some(solved) = self.submissions(day, part).find(|s| s.verdict == Verdict::Correct) {
+            return if solved.answer == answer {
+                Ok(Verdict::Correct)
+            } else {
+                Err(Error::AlreadySolved(solved.answer.clone()))
+            };
+        }
+        if let Some(earlier) = self.submissions(day, part).find(|earlier| earlier.rules_out(answer)) {
+            return Err(Error::KnownWrong(earlier.clone()));
+        }
+        let now = unix_time();
+        if let Some(wait_until) = self.log.wait_until.filter(|&wait_until| wait_until > now) {
+            return Err(Error::Throttled(Duration::from_secs(wait_until - now)));
+        }
+
+        let page = self
+            .agent
+            .post(&self.url(day, "answer"))
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?
+            .into_string()?;
+        let wait_from_now = |wait: Duration| now + wait.as_secs();
+        match Response::parse(&page) {
+            Response::Verdict(verdict, wait) => {
+                let submission = Submission {
+                    day,
+                    part,
+                    answer: answer.to_owned(),
+                    verdict,
+                };
+                self.log.wait_until = wait.map(wait_from_now);
+                self.log.submissions.push(submission.clone());
+                self.save()?;
+                self.record_bound(&submission)?;
+                Ok(verdict)
+            }
+            Response::Throttled(wait) => {
+                self.log.wait_until = Some(wait_from_now(wait));
+                self.save()?;
+                Err(Error::Throttled(wait))
+            }
+            Response::WrongLevel => Err(Error::WrongLevel),
+            Response::Unrecognized => Err(Error::Unrecognized(page)),
+        }
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        fs::write(&self.log_path, serde_json::to_string_pretty(&self.log)?)?;
+        Ok(())
+    }
+
+    fn record_bound(&self, submission: &Submission) -> Result<(), Error> {
+        let Some(bounds_path) = &self.bounds_path else {
+            return Ok(());
+        };
+        let registry = match fs::read_to_string(bounds_path) {
+            Ok(registry) => registry,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(Error::Bounds(err.to_string())),
+        };
+        let registry = record_bound(&registry, submission).map_err(Error::Bounds)?;
+        fs::write(bounds_path, registry).map_err(|err| Error::Bounds(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, thread};
+
+    use super::*;
+
+    const RIGHT: &str = "<article><p>That's the right answer! You are one gold star closer to saving Christmas.</p>";
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high. If you're stuck, make \
+                            sure you're using the full input data. Please wait one minute before trying again.</p>";
+    const TOO_SOON: &str = "<article><p>You gave an answer too recently; you have to wait after submitting an answer \
+                            before trying again.  You have 1m 5s left to wait.</p>";
+    const WRONG_LEVEL: &str = "<article><p>You don't seem to be solving the right level.  Did you already complete it?";
+
+    /// Answer requests on a local server with each of `pages` in turn, returning where to find it and a summary of
+    /// every request it got. It gives up waiting for more after a bit, for the tests that expect fewer requests.
+    fn serve(pages: &[&'static str]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let pages = pages.to_vec();
+        let requests = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for page in pages {
+                let Some(mut request) = server.recv_timeout(Duration::from_secs(2)).unwrap() else {
+                    break;
+                };
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let cookie = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Cookie"))
+                    .unwrap();
+                requests.push(format!(
+                    "{} {} {} {body}",
+                    request.method(),
+                    request.url(),
+                    cookie.value
+                ));
+                request.respond(tiny_http::Response::from_string(page)).unwrap();
+            }
+            requests
+        });
+        (url, requests)
+    }
+
+    fn client(url: &str, log_path: &Path) -> Client {
+        Client::new(2023, "cookie", log_path).unwrap().with_base_url(url)
+    }
+
+    #[test]
+    fn fetches_inputs() {
+        let (url, requests) = serve(&["1abc2\n"]);
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("submissions.json");
+        assert_eq!(client(&url, &log_path).input(1).unwrap(), "1abc2\n");
+        assert_eq!(requests.join().unwrap(), ["GET /2023/day/1/input session=cookie "]);
+    }
+
+    #[test]
+    fn remembers_wrong_answers() {
+        let (url, requests) = serve(&[TOO_HIGH]);
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("submissions.json");
+        let bounds_path = dir.path().join("answer_bounds.toml");
+        fs::write(&bounds_path, "# Wrong answers.\n\n[day13.part2]\ntoo_low = [10000]\n").unwrap();
+        let mut client = client(&url, &log_path).with_answer_bounds(&bounds_path);
+        assert_eq!(client.submit(5, 1, "100\n").unwrap(), Verdict::TooHigh);
+        assert_eq!(
+            fs::read_to_string(&bounds_path).unwrap(),
+            "# Wrong answers.\n\n[day13.part2]\ntoo_low = [10000]\n\n[day05.part1]\ntoo_high = [100]\n"
+        );
+        assert!(matches!(client.submit(5, 1, "100"), Err(Error::KnownWrong(earlier)) if earlier.answer == "100"));
+        assert!(matches!(client.submit(5, 1, "150"), Err(Error::KnownWrong(_))));
+        // Lower might be right, but the site asked to wait a minute first.
+        assert!(matches!(client.submit(5, 1, "50"), Err(Error::Throttled(wait)) if wait <= Duration::from_secs(60)));
+        assert_eq!(
+            requests.join().unwrap(),
+            ["POST /2023/day/5/answer session=cookie level=1&answer=100"]
+        );
+
+        // Which is all still known the next time round.
+        let mut client = Client::new(2023, "cookie", &log_path).unwrap().with_base_url(url);
+        assert_eq!(client.submissions(5, 1).count(), 1);
+        assert_eq!(client.submissions(5, 2).count(), 0);
+        assert!(matches!(client.submit(5, 1, "100"), Err(Error::KnownWrong(_))));
+        assert!(matches!(client.submit(5, 1, "50"), Err(Error::Throttled(_))));
+    }
+
+    #[test]
+    fn remembers_right_answers() {
+        let (url, requests) = serve(&[RIGHT, WRONG_LEVEL, TOO_SOON]);
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("submissions.json");
+        let mut client = client(&url, &log_path);
+        assert_eq!(client.submit(7, 1, "42").unwrap(), Verdict::Correct);
+        assert_eq!(client.submit(7, 1, "42").unwrap(), Verdict::Correct);
+        assert!(matches!(client.submit(7, 1, "43"), Err(Error::AlreadySolved(answer)) if answer == "42"));
+
+        assert!(matches!(client.submit(7, 2, "abc"), Err(Error::WrongLevel)));
+        assert!(matches!(client.submit(7, 2, "abc"), Err(Error::Throttled(wait)) if wait == Duration::from_secs(65)));
+        assert!(matches!(client.submit(7, 2, "def"), Err(Error::Throttled(_))));
+        assert_eq!(
+            requests.join().unwrap(),
+            [
+                "POST /2023/day/7/answer session=cookie level=1&answer=42",
+                "POST /2023/day/7/answer session=cookie level=2&answer=abc",
+                "POST /2023/day/7/answer session=cookie level=2&answer=abc",
+            ]
+        );
+    }
+
+    #[test]
+    fn records_bounds() {
+        let submission = |part, answer: &str, verdict| Submission {
+            day: 13,
+            part,
+            answer: answer.to_owned(),
+            verdict,
+        };
+        let registry = "[day13.part2]\ntoo_low = [10000]\ntoo_high = [35975]\n";
+        assert_eq!(
+            record_bound(registry, &submission(2, "20000", Verdict::TooLow)).unwrap(),
+            "[day13.part2]\ntoo_low = [10000, 20000]\ntoo_high = [35975]\n"
+        );
+        assert_eq!(
+            record_bound(registry, &submission(2, "35975", Verdict::TooHigh)).unwrap(),
+            registry
+        );
+        assert_eq!(
+            record_bound(registry, &submission(1, "abc", Verdict::Wrong)).unwrap(),
+            "[day13.part2]\ntoo_low = [10000]\ntoo_high = [35975]\n\n[day13.part1]\nwrong = [\"abc\"]\n"
+        );
+        assert_eq!(
+            record_bound(registry, &submission(2, "30000", Verdict::Correct)).unwrap(),
+            registry
+        );
+        assert!(record_bound("day13 = 5", &submission(2, "1", Verdict::Wrong)).is_err());
+    }
+
+    #[test]
+    fn reads_waits() {
+        assert_eq!(minutes_to_wait(TOO_HIGH), Some(Duration::from_secs(60)));
+        assert_eq!(
+            minutes_to_wait("please wait 5 minutes before trying again."),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(time_left(TOO_SOON), Some(Duration::from_secs(65)));
+        assert_eq!(time_left("You have 34s left to wait."), Some(Duration::from_secs(34)));
+        assert_eq!(time_left("You have 1h 2é left to wait."), None);
+        assert_eq!(Response::parse("<html>Oops</html>"), Response::Unrecognized);
+        let page = "<html><main><article><p>Something   <em>new</em>\nhappened.</p></article></main></html>";
+        assert_eq!(
+            Error::Unrecognized(page.to_owned()).to_string(),
+            "could not make out what the site said: Something new happened."
+        );
+        assert_eq!(snippet(&"word ".repeat(50)).chars().count(), 101);
+    }
+}
@@ -0,0 +1,67 @@
+//! Reading back the estimates criterion saves for each benchmark under `target/criterion`.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// Where criterion keeps its results, found the way criterion itself finds it.
+pub fn criterion_home() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return home.into();
+    }
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("target"));
+    target.join("criterion")
+}
+
+/// Turn part of a benchmark ID into the directory name criterion saves it under.
+pub fn directory_name(id: &str) -> String {
+    id.replace(['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'], "_")
+}
+
+/// The time criterion reports for the benchmark saved in `dir`, from the run saved as `baseline`. The latest run is
+/// always saved as `new`.
+pub fn typical(dir: &Path, baseline: &str) -> Option<Duration> {
+    let estimates = std::fs::read(dir.join(baseline).join("estimates.json")).ok()?;
+    let estimates: serde_json::Value = serde_json::from_slice(&estimates).ok()?;
+    // Like criterion, prefer the slope of the linear regression when there is one.
+    let estimate = match &estimates["slope"] {
+        serde_json::Value::Null => &estimates["mean"],
+        slope => slope,
+    };
+    Some(Duration::from_secs_f64(estimate["point_estimate"].as_f64()? / 1e9))
+}
+
+/// Like [`typical`] for the latest run, but only if that run finished no earlier than `since`, so that results left
+/// over from an earlier run of the benchmarks don't get mixed in.
+pub fn typical_since(dir: &Path, since: SystemTime) -> Option<Duration> {
+    let modified = std::fs::metadata(dir.join("new").join("estimates.json"))
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    (modified >= since).then(|| typical(dir, "new")).flatten()
+}
+
+pub fn median(mut timings: Vec<Duration>) -> Option<Duration> {
+    timings.sort_unstable();
+    let middle = timings.len() / 2;
+    match timings.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((timings[middle - 1] + timings[middle]) / 2),
+        _ => Some(timings[middle]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn medians() {
+        let ms = Duration::from_millis;
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![ms(3), ms(1), ms(2)]), Some(ms(2)));
+        assert_eq!(median(vec![ms(4), ms(1), ms(2), ms(8)]), Some(ms(3)));
+    }
+}
@@ -1 +1,206 @@
-// this space intentionally left blank
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+pub use aoc_common::{InputError, Solution};
+
+#[cfg(feature = "count-allocations")]
+pub mod allocations;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod estimates;
+pub mod metadata;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+
+impl Part {
+    pub fn includes_part1(self) -> bool {
+        self != Part::Two
+    }
+
+    pub fn includes_part2(self) -> bool {
+        self != Part::One
+    }
+}
+
+/// One of the steps solving a day is made of, benchmarked on their own to tell parsing apart from the algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::Part1, Phase::Part2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        }
+    }
+}
+
+/// The outcome of running a day: how long parsing took, then each requested part's answer and how long it took.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub parse: Duration,
+    pub part1: Option<(String, Duration)>,
+    pub part2: Option<(String, Duration)>,
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub fn run<S: Solution>(input: &str, part: Part) -> Result<Run, InputError> {
+    let (input, parse) = time(|| S::parse(input));
+    let input = input?;
+    S::check_assumptions(&input)?;
+    let part1 = part
+        .includes_part1()
+        .then(|| {
+            let (answer, elapsed) = time(|| S::part1(&input));
+            answer.map(|answer| (answer.to_string(), elapsed))
+        })
+        .transpose()?;
+    let part2 = part
+        .includes_part2()
+        .then(|| {
+            let (answer, elapsed) = time(|| S::part2(&input));
+            answer.map(|answer| (answer.to_string(), elapsed))
+        })
+        .transpose()?;
+    Ok(Run { parse, part1, part2 })
+}
+
+/// Parse `input` and solve both parts, like [`Solution::solve`] but without checking the input's assumptions first:
+/// that's done once by whatever runs the answers, and shouldn't be timed over and over along with the solving.
+fn solve_unchecked<S: Solution>(input: &str) -> Result<(S::Part1, S::Part2), InputError> {
+    let input = S::parse(input)?;
+    Ok((S::part1(&input)?, S::part2(&input)?))
+}
+
+fn solve_embedded<S: Solution>() {
+    black_box(solve_unchecked::<S>(black_box(S::INPUT))).expect("embedded input should be solvable");
+}
+
+/// Something that benchmarks the routine it's handed, like a wrapped criterion `Bencher`.
+pub type Bench<'a> = dyn FnMut(&mut dyn FnMut()) + 'a;
+
+fn solve_with<S: Solution>(input: &str) -> Result<(), InputError> {
+    black_box(solve_unchecked::<S>(black_box(input))).map(drop)
+}
+
+fn parse_embedded<S: Solution>() -> S::Input<'static> {
+    S::parse(black_box(S::INPUT)).expect("embedded input should parse")
+}
+
+fn run_parse<S: Solution>() {
+    black_box(parse_embedded::<S>());
+}
+
+fn run_part1<S: Solution>() {
+    black_box(S::part1(&parse_embedded::<S>())).expect("embedded input should be solvable");
+}
+
+fn run_part2<S: Solution>() {
+    black_box(S::part2(&parse_embedded::<S>())).expect("embedded input should be solvable");
+}
+
+fn bench_phase<S: Solution>(phase: Phase, bench: &mut Bench) {
+    match phase {
+        Phase::Parse => bench(&mut run_parse::<S>),
+        Phase::Part1 => {
+            let input = parse_embedded::<S>();
+            bench(&mut || {
+                black_box(S::part1(black_box(&input))).expect("embedded input should be solvable");
+            });
+        }
+        Phase::Part2 => {
+            let input = parse_embedded::<S>();
+            bench(&mut || {
+                black_box(S::part2(black_box(&input))).expect("embedded input should be solvable");
+            });
+        }
+    }
+}
+
+pub struct Day {
+    pub name: &'static str,
+    pub input: &'static str,
+    /// Solve both parts of the embedded input, for benchmarking.
+    pub solve: fn(),
+    /// Solve both parts of any input, for benchmarking.
+    pub solve_input: fn(&str) -> Result<(), InputError>,
+    pub run: fn(&str, Part) -> Result<Run, InputError>,
+    /// Run each of [`Phase::ALL`] on the embedded input from scratch, for benchmarkers like iai that can't keep setup
+    /// out of the measurement. The parts have to parse the input first, so their timings include parsing.
+    pub phases: [fn(); 3],
+    /// Hand `bench` a routine running one phase on the embedded input, with the parsing the parts need already done.
+    pub bench_phase: fn(Phase, &mut Bench),
+}
+
+impl Day {
+    const fn new<S: Solution>(name: &'static str) -> Self {
+        Self {
+            name,
+            input: S::INPUT,
+            solve: solve_embedded::<S>,
+            solve_input: solve_with::<S>,
+            run: run::<S>,
+            phases: [run_parse::<S>, run_part1::<S>, run_part2::<S>],
+            bench_phase: bench_phase::<S>,
+        }
+    }
+}
+
+macro_rules! days {
+    ($($day:ident),+$(,)?) => {
+        pub const DAYS: &[Day] = &[$(Day::new::<$day::Puzzle>(stringify!($day))),+];
+    };
+}
+
+pub fn find_day(name: &str) -> Option<&'static Day> {
+    let number = name.strip_prefix("day").unwrap_or(name).parse::<usize>().ok()?;
+    DAYS.get(number.checked_sub(1)?)
+}
+
+#[rustfmt::skip]
+days!(
+    day01,
+    day02,
+    day03,
+    day04,
+    day05,
+    day06,
+    day07,
+    day08,
+    day09,
+    day10,
+    day11,
+    day12,
+    day13,
+    day14,
+    day15,
+    day16,
+    day17,
+    day18,
+    day19,
+    day20,
+    day21,
+    day22,
+    day23,
+    day24,
+    day25,
+);
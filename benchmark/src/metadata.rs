@@ -0,0 +1,119 @@
+//! The notes kept on each day in the workspace manifest, under `[workspace.metadata.dayNN]`.
+
+use std::{convert::TryFrom, time::Duration};
+
+use toml::value::{Datetime, Offset};
+
+const MANIFEST: &str = include_str!("../../Cargo.toml");
+
+fn workspace_metadata() -> Option<toml::Table> {
+    let manifest = MANIFEST
+        .parse::<toml::Table>()
+        .expect("workspace manifest should be valid TOML");
+    manifest.get("workspace")?.get("metadata")?.as_table().cloned()
+}
+
+/// Which year's puzzles these are.
+pub fn year() -> i64 {
+    workspace_metadata()
+        .and_then(|metadata| metadata.get("year")?.as_integer())
+        .expect("workspace manifest should have a year")
+}
+
+/// What the workspace manifest says about a day. Days from before these were recorded have neither time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayMetadata {
+    /// When work on the day started.
+    pub start_time: Option<Datetime>,
+    /// When both parts were solved.
+    pub completion_time: Option<Datetime>,
+}
+
+impl DayMetadata {
+    /// Look up the metadata for `day`, like `"day07"`.
+    pub fn of(day: &str) -> Self {
+        let Some(metadata) = workspace_metadata().and_then(|metadata| metadata.get(day).cloned()) else {
+            return Self::default();
+        };
+        let time = |key| metadata.get(key).and_then(toml::Value::as_datetime).copied();
+        Self {
+            start_time: time("start_time"),
+            completion_time: time("completion_time"),
+        }
+    }
+
+    /// How long it took to solve the day, from starting on it to solving both parts.
+    pub fn time_to_solve(&self) -> Option<Duration> {
+        let start = nanos_since_epoch(&self.start_time?)?;
+        let completion = nanos_since_epoch(&self.completion_time?)?;
+        Some(Duration::from_nanos(
+            u64::try_from(completion.checked_sub(start)?).ok()?,
+        ))
+    }
+}
+
+/// The nanoseconds between the Unix epoch and `datetime`, which needs both a date and a time. Times without an offset
+/// are all taken to be in the same timezone, so they can only be compared against each other.
+fn nanos_since_epoch(datetime: &Datetime) -> Option<i128> {
+    let (date, time) = (datetime.date?, datetime.time?);
+
+    // Howard Hinnant's `days_from_civil`, counting from a March 1st so that leap days come last in the year.
+    let (year, month, day) = (i64::from(date.year), i64::from(date.month), i64::from(date.day));
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let offset_minutes = match datetime.offset {
+        None | Some(Offset::Z) => 0,
+        Some(Offset::Custom { minutes }) => i64::from(minutes),
+    };
+    let seconds = days * 86_400 + i64::from(time.hour) * 3600 + i64::from(time.minute) * 60 + i64::from(time.second)
+        - offset_minutes * 60;
+    Some(i128::from(seconds) * 1_000_000_000 + i128::from(time.nanosecond))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(s: &str) -> Option<Datetime> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn reads_times_from_the_manifest() {
+        let day07 = DayMetadata::of("day07");
+        assert_eq!(day07.start_time, datetime("2023-12-07T13:09:06.965940"));
+        assert_eq!(day07.completion_time, datetime("2023-12-07T13:47:10.350318"));
+        assert_eq!(DayMetadata::of("day01"), DayMetadata::default());
+        assert_eq!(year(), 2023);
+    }
+
+    #[test]
+    fn times_to_solve() {
+        let metadata = |start, completion| DayMetadata {
+            start_time: datetime(start),
+            completion_time: datetime(completion),
+        };
+        assert_eq!(
+            metadata("2023-12-07T13:09:06.5", "2023-12-07T13:47:10.75").time_to_solve(),
+            Some(Duration::from_millis(38 * 60_000 + 4250))
+        );
+        assert_eq!(
+            metadata("2024-02-28T23:00:00", "2024-03-01T01:00:00").time_to_solve(),
+            Some(Duration::from_secs(26 * 3600))
+        );
+        assert_eq!(
+            metadata("2023-12-31T23:30:00Z", "2024-01-01T01:00:00+01:00").time_to_solve(),
+            Some(Duration::from_secs(30 * 60))
+        );
+        assert_eq!(
+            metadata("2023-12-08T10:00:00", "2023-12-07T10:00:00").time_to_solve(),
+            None
+        );
+        assert_eq!(DayMetadata::of("day06").time_to_solve(), None);
+    }
+}
@@ -0,0 +1,45 @@
+//! Checks every day against the known right answers for its input, kept in the workspace manifest as
+//! `answers = [part1, part2]` under `[workspace.metadata.dayNN]`.
+
+use aoc_benchmark::{Part, DAYS};
+
+/// Day 25 samples random paths through the graph and only gets the right answer most of the time.
+const RANDOMIZED_ATTEMPTS: usize = 5;
+
+fn known_answers() -> toml::Table {
+    let manifest = include_str!("../../Cargo.toml").parse::<toml::Table>().unwrap();
+    manifest["workspace"]["metadata"].as_table().unwrap().clone()
+}
+
+#[test]
+fn answers_are_unchanged() {
+    let known_answers = known_answers();
+
+    let mut mismatches = Vec::new();
+    for day in DAYS {
+        let expected = known_answers
+            .get(day.name)
+            .and_then(|metadata| metadata.get("answers"))
+            .and_then(|answers| answers.as_array())
+            .unwrap_or_else(|| panic!("no known answers for {}", day.name))
+            .iter()
+            .map(|answer| answer.as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+
+        let attempts = if day.name == "day25" { RANDOMIZED_ATTEMPTS } else { 1 };
+        let mut actual = Vec::new();
+        for _ in 0..attempts {
+            let run = (day.run)(day.input, Part::Both).unwrap();
+            actual = vec![run.part1.unwrap().0, run.part2.unwrap().0];
+            if actual == expected {
+                break;
+            }
+        }
+
+        if actual != expected {
+            mismatches.push(format!("{}: expected {expected:?}, got {actual:?}", day.name));
+        }
+    }
+
+    assert!(mismatches.is_empty(), "answers changed:\n{}", mismatches.join("\n"));
+}
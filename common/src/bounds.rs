@@ -0,0 +1,126 @@
+//! The registry of wrong answers in `answer_bounds.toml`, at the root of the workspace.
+
+use std::fmt::Display;
+
+const REGISTRY: &str = include_str!("../../answer_bounds.toml");
+
+/// What the site said about wrong answers to one part of a day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnswerBounds {
+    /// Answers that were too low, so the right one is above all of them.
+    pub too_low: Vec<i128>,
+    /// Answers that were too high, so the right one is below all of them.
+    pub too_high: Vec<i128>,
+    /// Answers that were wrong, without saying which way.
+    pub wrong: Vec<String>,
+}
+
+impl AnswerBounds {
+    /// What the registry says about `part` of `day`, like `"day13"`.
+    ///
+    /// # Panics
+    ///
+    /// If the registry is malformed.
+    pub fn of(day: &str, part: u8) -> Self {
+        Self::from_registry(REGISTRY, day, part).unwrap_or_else(|err| panic!("answer_bounds.toml is malformed: {err}"))
+    }
+
+    fn from_registry(registry: &str, day: &str, part: u8) -> Result<Self, String> {
+        let registry = registry.parse::<toml::Table>().map_err(|err| err.to_string())?;
+        let Some(known) = registry.get(day).and_then(|day| day.get(format!("part{part}"))) else {
+            return Ok(Self::default());
+        };
+
+        let answers = |key: &str| match known.get(key) {
+            None => Ok(Vec::new()),
+            Some(toml::Value::Array(answers)) => answers
+                .iter()
+                .map(|answer| match answer {
+                    toml::Value::Integer(answer) => Ok(answer.to_string()),
+                    toml::Value::String(answer) => Ok(answer.clone()),
+                    other => Err(format!("{day}.part{part}.{key} should hold answers, not {other}")),
+                })
+                .collect::<Result<Vec<_>, _>>(),
+            Some(other) => Err(format!("{day}.part{part}.{key} should be a list, not {other}")),
+        };
+        let numbers = |key: &str| {
+            answers(key)?
+                .iter()
+                .map(|answer| {
+                    answer
+                        .parse::<i128>()
+                        .map_err(|_| format!("{day}.part{part}.{key} should hold numbers, not {answer:?}"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            too_low: numbers("too_low")?,
+            too_high: numbers("too_high")?,
+            wrong: answers("wrong")?,
+        })
+    }
+
+    /// Why `answer` can't be right, if these rule it out.
+    pub fn rule_out(&self, answer: &str) -> Option<String> {
+        if self.wrong.iter().any(|wrong| wrong == answer) {
+            return Some("it was already wrong".to_owned());
+        }
+        let answer = answer.parse::<i128>().ok()?;
+        if let Some(low) = self.too_low.iter().filter(|&&low| answer <= low).max() {
+            return Some(format!("{low} was too low"));
+        }
+        if let Some(high) = self.too_high.iter().filter(|&&high| answer >= high).min() {
+            return Some(format!("{high} was too high"));
+        }
+        None
+    }
+}
+
+/// Warn about each of `day`'s answers that the registry rules out, so it doesn't get submitted again.
+pub fn warn_if_ruled_out(day: &str, part1: impl Display, part2: impl Display) {
+    for (part, answer) in [(1, part1.to_string()), (2, part2.to_string())] {
+        if let Some(reason) = AnswerBounds::of(day, part).rule_out(&answer) {
+            eprintln!("warning: {answer} can't be the answer to part {part}, as {reason}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_out_known_wrong_answers() {
+        let registry = "[day01.part2]\ntoo_low = [10, 20]\ntoo_high = [\"50\"]\nwrong = [30, \"abc\"]";
+        let bounds = AnswerBounds::from_registry(registry, "day01", 2).unwrap();
+        assert_eq!(bounds.rule_out("15").as_deref(), Some("20 was too low"));
+        assert_eq!(bounds.rule_out("50").as_deref(), Some("50 was too high"));
+        assert_eq!(bounds.rule_out("abc").as_deref(), Some("it was already wrong"));
+        assert_eq!(bounds.rule_out("30").as_deref(), Some("it was already wrong"));
+        assert_eq!(bounds.rule_out("21"), None);
+        assert_eq!(bounds.rule_out("def"), None);
+
+        assert_eq!(
+            AnswerBounds::from_registry(registry, "day01", 1),
+            Ok(AnswerBounds::default())
+        );
+        assert!(AnswerBounds::from_registry("[day01.part1]\ntoo_low = [\"x\"]", "day01", 1).is_err());
+        assert!(AnswerBounds::from_registry("[day01.part1]\nwrong = 3", "day01", 1).is_err());
+    }
+
+    #[test]
+    fn known_answers_are_not_ruled_out() {
+        let manifest = include_str!("../../Cargo.toml").parse::<toml::Table>().unwrap();
+        for (day, metadata) in manifest["workspace"]["metadata"].as_table().unwrap() {
+            let Some(answers) = metadata.get("answers").and_then(toml::Value::as_array) else {
+                continue;
+            };
+            for (part, answer) in (1..).zip(answers) {
+                let answer = answer.as_str().unwrap();
+                assert_eq!(AnswerBounds::of(day, part).rule_out(answer), None, "{day} part {part}");
+            }
+        }
+        assert_ne!(AnswerBounds::of("day13", 2), AnswerBounds::default());
+    }
+}
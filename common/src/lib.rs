@@ -0,0 +1,221 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+pub mod bounds;
+
+/// The shape every day's solution shares: parse the puzzle input once, then solve each part from the parsed form.
+pub trait Solution {
+    /// The puzzle input embedded in the day's crate.
+    const INPUT: &'static str;
+
+    /// The parsed puzzle input, which may borrow from the raw text.
+    type Input<'a>;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError>;
+    /// Solve part 1. Like [`Solution::check_assumptions`], this reports an input without the properties the solution
+    /// relies on, for the ones that only come to light while solving.
+    fn part1(input: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption>;
+    fn part2(input: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption>;
+
+    /// Check that the input has the properties of the official inputs the solution relies on, so that one without
+    /// them gets reported instead of getting a wrong answer. The examples from the puzzle statement often lack them,
+    /// which is why this is kept apart from [`Solution::parse`]. This runs before either part, so it should be cheap
+    /// next to solving them; anything that would take solving to find out is left for the parts to report.
+    fn check_assumptions(_input: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        Ok(())
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let input = Self::parse(input)?;
+        Self::check_assumptions(&input)?;
+        Ok((Self::part1(&input)?, Self::part2(&input)?))
+    }
+}
+
+/// Why a puzzle input could not be parsed, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line the problem was found on.
+    pub line: usize,
+    /// The 1-based column, counted in characters, the problem was found at.
+    pub column: usize,
+    pub reason: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.reason)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A property of the official puzzle inputs that a solution relies on, but that this input lacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenAssumption {
+    /// What the input should look like, but doesn't.
+    pub reason: String,
+}
+
+impl BrokenAssumption {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self { reason: reason.into() }
+    }
+}
+
+impl Display for BrokenAssumption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported input: {}", self.reason)
+    }
+}
+
+impl Error for BrokenAssumption {}
+
+/// Why a puzzle input could not be solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    Parse(ParseError),
+    Assumption(BrokenAssumption),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(err) => Display::fmt(err, f),
+            Self::Assumption(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err),
+            Self::Assumption(err) => Some(err),
+        }
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<BrokenAssumption> for InputError {
+    fn from(err: BrokenAssumption) -> Self {
+        Self::Assumption(err)
+    }
+}
+
+/// A problem found while parsing, still pointing at the offending text.
+///
+/// Parsers deal in slices of the input, so they report these and leave it to [`Unexpected::locate`] to turn the slice
+/// into a line and column once the whole input is at hand again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unexpected<'a> {
+    pub at: &'a str,
+    pub reason: String,
+}
+
+impl<'a> Unexpected<'a> {
+    pub fn new(at: &'a str, reason: impl Into<String>) -> Self {
+        Self {
+            at,
+            reason: reason.into(),
+        }
+    }
+
+    /// Find where in `input` the offending text is. If it isn't part of `input` at all, the error is reported at the
+    /// very end of it.
+    pub fn locate(self, input: &str) -> ParseError {
+        let offset = (self.at.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| offset <= input.len() && input.is_char_boundary(offset))
+            .unwrap_or(input.len());
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            reason: self.reason,
+        }
+    }
+}
+
+/// Fallible versions of the `str` methods parsers lean on, reporting what they expected to find.
+pub trait ParseExt {
+    fn try_split_once<'a>(&'a self, delimiter: &str) -> Result<(&'a str, &'a str), Unexpected<'a>>;
+
+    fn try_parse<T>(&self) -> Result<T, Unexpected<'_>>
+    where
+        T: FromStr,
+        T::Err: Display;
+}
+
+impl ParseExt for str {
+    fn try_split_once<'a>(&'a self, delimiter: &str) -> Result<(&'a str, &'a str), Unexpected<'a>> {
+        self.split_once(delimiter)
+            .ok_or_else(|| Unexpected::new(self, format!("expected {delimiter:?}")))
+    }
+
+    fn try_parse<T>(&self) -> Result<T, Unexpected<'_>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.parse()
+            .map_err(|err| Unexpected::new(self, format!("invalid number {self:?}: {err}")))
+    }
+}
+
+/// Solve `a` and `b` at the same time on the rayon thread pool, or one after the other without the `parallel` feature.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    return (a(), b());
+}
+
+/// Run one of the worked examples from the puzzle statement through `S`, checking its answers. Examples don't always
+/// come with an answer for both parts, so the ones given as `None` are skipped.
+#[track_caller]
+pub fn check_example<S: Solution>(input: &str, part1: Option<&str>, part2: Option<&str>) {
+    let input = S::parse(input).unwrap_or_else(|err| panic!("example should parse, but: {err}"));
+    if let Some(expected) = part1 {
+        let answer = S::part1(&input).unwrap_or_else(|err| panic!("part 1 should solve the example, but: {err}"));
+        assert_eq!(answer.to_string(), expected, "wrong answer for part 1");
+    }
+    if let Some(expected) = part2 {
+        let answer = S::part2(&input).unwrap_or_else(|err| panic!("part 2 should solve the example, but: {err}"));
+        assert_eq!(answer.to_string(), expected, "wrong answer for part 2");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_fragments() {
+        let input = "abc\ndéf\nghi";
+        let line = input.lines().nth(1).unwrap();
+        let err = Unexpected::new(&line[3..], "oops").locate(input);
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.to_string(), "line 2, column 3: oops");
+
+        let err = Unexpected::new("elsewhere", "oops").locate(input);
+        assert_eq!((err.line, err.column), (3, 4));
+    }
+}
@@ -1,43 +1,97 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+
 const DIGITS: [&[u8]; 9] = [
     b"one", b"two", b"three", b"four", b"five", b"six", b"seven", b"eight", b"nine",
 ];
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-
-    let part1 = input
-        .lines()
-        .map(|line| {
-            let mut it = line.bytes().filter(|ch| ch.is_ascii_digit());
-            let first = it.clone().next().unwrap();
-            let second = it.next_back().unwrap();
-            usize::from(10 * (first - b'0') + (second - b'0'))
-        })
-        .sum::<usize>();
-
-    let part2 = input
-        .lines()
-        .map(|line| {
-            let mut line = line.as_bytes();
-            let mut first = None;
-            let mut last = None;
-            while !line.is_empty() {
-                let digit = if line[0].is_ascii_digit() {
-                    Some(usize::from(line[0] - b'0'))
-                } else {
-                    DIGITS.iter().position(|&d| line.starts_with(d)).map(|n| n + 1)
-                };
-                first = first.or(digit);
-                last = digit.or(last);
-                line = &line[1..];
+/// The value of the digit, spelled out or not, that `line` starts with.
+fn leading_digit(line: &[u8]) -> Option<usize> {
+    if line[0].is_ascii_digit() {
+        Some(usize::from(line[0] - b'0'))
+    } else {
+        DIGITS.iter().position(|&d| line.starts_with(d)).map(|n| n + 1)
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = &'a str;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        for line in input.lines() {
+            let bytes = line.as_bytes();
+            if !(0..bytes.len()).any(|idx| leading_digit(&bytes[idx..]).is_some()) {
+                return Err(Unexpected::new(line, "line has no digits").locate(input));
             }
+        }
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(input
+            .lines()
+            .map(|line| {
+                // Lines that only spell their digits out don't count towards the first part.
+                let mut it = line.bytes().filter(|ch| ch.is_ascii_digit());
+                let (Some(first), Some(second)) = (it.clone().next(), it.next_back()) else {
+                    return 0;
+                };
+                usize::from(10 * (first - b'0') + (second - b'0'))
+            })
+            .sum::<usize>())
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(input
+            .lines()
+            .map(|line| {
+                let mut line = line.as_bytes();
+                let mut first = None;
+                let mut last = None;
+                while !line.is_empty() {
+                    let digit = leading_digit(line);
+                    first = first.or(digit);
+                    last = digit.or(last);
+                    line = &line[1..];
+                }
+
+                // `parse` made sure every line has at least one digit.
+                first.unwrap() * 10 + last.unwrap()
+            })
+            .sum::<usize>())
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
 
-            first.unwrap() * 10 + last.unwrap()
-        })
-        .sum::<usize>();
+    #[test]
+    fn part1_example() {
+        check_example::<Puzzle>(include_str!("sample_input_2.txt"), Some("142"), None);
+    }
 
-    (part1, part2)
+    #[test]
+    fn part2_example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), None, Some("281"));
+    }
 }
@@ -1,45 +1,101 @@
 use std::fmt::Display;
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let games = input.lines().map(|game| {
-        let (id, reveals) = game.split_once(": ").unwrap();
-
-        (
-            id.split_once(' ').unwrap().1.parse::<u32>().unwrap(),
-            reveals.split("; ").map(|reveal| {
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+
+type Reveal = (u8, u8, u8);
+
+pub struct Game {
+    id: u32,
+    reveals: Vec<Reveal>,
+}
+
+fn parse_game(game: &str) -> Result<Game, Unexpected<'_>> {
+    let (id, reveals) = game.try_split_once(": ")?;
+
+    Ok(Game {
+        id: id.try_split_once(" ")?.1.try_parse()?,
+        reveals: reveals
+            .split("; ")
+            .map(|reveal| {
                 let mut red = 0;
                 let mut green = 0;
                 let mut blue = 0;
                 for s in reveal.split(", ") {
-                    let (n, color) = s.split_once(' ').unwrap();
-                    let n = n.parse::<u8>().unwrap();
+                    let (n, color) = s.try_split_once(" ")?;
+                    let n = n.try_parse::<u8>()?;
                     match color {
                         "red" => red = n,
                         "green" => green = n,
                         "blue" => blue = n,
-                        _ => panic!("Unknown color {}", color),
+                        _ => return Err(Unexpected::new(color, format!("unknown color {color:?}"))),
                     }
                 }
-                (red, green, blue)
-            }),
-        )
-    });
-
-    let part1 = games
-        .clone()
-        .filter_map(|(id, mut reveals)| reveals.all(|(r, g, b)| r <= 12 && g <= 13 && b <= 14).then_some(id))
-        .sum::<u32>();
-
-    let part2 = games
-        .map(|(_id, reveals)| {
-            let (r, g, b) = reveals.fold((0, 0, 0), |(r1, g1, b1), (r2, g2, b2)| {
-                (r1.max(r2), g1.max(g2), b1.max(b2))
-            });
-            u32::from(r) * u32::from(g) * u32::from(b)
-        })
-        .sum::<u32>();
-
-    (part1, part2)
+                Ok((red, green, blue))
+            })
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Vec<Game>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .lines()
+            .map(parse_game)
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(games: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(games
+            .iter()
+            .filter_map(|game| {
+                game.reveals
+                    .iter()
+                    .all(|&(r, g, b)| r <= 12 && g <= 13 && b <= 14)
+                    .then_some(game.id)
+            })
+            .sum::<u32>())
+    }
+
+    fn part2(games: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(games
+            .iter()
+            .map(|game| {
+                let (r, g, b) = game.reveals.iter().fold((0, 0, 0), |(r1, g1, b1), &(r2, g2, b2)| {
+                    (r1.max(r2), g1.max(g2), b1.max(b2))
+                });
+                u32::from(r) * u32::from(g) * u32::from(b)
+            })
+            .sum::<u32>())
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("8"), Some("2286"));
+    }
 }
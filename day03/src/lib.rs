@@ -1,94 +1,119 @@
 use std::fmt::Display;
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input: &str = include_str!("input.txt");
-
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
-
-    let mut symbols = vec![false; width * height];
-    let mut gears = vec![None; width * height];
-    input.lines().enumerate().for_each(|(y, row)| {
-        row.bytes().enumerate().for_each(|(x, b)| {
-            if !matches!(b, b'0'..=b'9' | b'.') {
-                symbols[y * width + x] = true;
-                if b == b'*' {
-                    // gears.insert((x, y), Some((None, None)));
-                    gears[y * width + x] = Some((None, None));
-                };
-            }
-        });
-    });
-
-    let mut part1 = 0;
-    let mut nearby_gears = Vec::new();
-
-    for (y, row) in input.lines().enumerate() {
-        let mut found_symbol_nearby = false;
-        let mut number_so_far = 0;
-
-        for (x, b) in row.bytes().enumerate() {
-            if b.is_ascii_digit() {
-                number_so_far = number_so_far * 10 + (b - b'0') as u64;
-
-                for dx in -1..=1 {
-                    for dy in -1..=1 {
-                        if let (Some(x), Some(y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
-                            if symbols.get(y * width + x).copied().unwrap_or_default() {
-                                found_symbol_nearby = true;
-                                if gears[y * width + x].is_some() {
-                                    nearby_gears.push((x, y));
-                                }
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+use aoc_grid::Grid;
+
+pub struct PartNumber {
+    value: u64,
+    nearby_gears: Vec<usize>,
+}
+
+pub struct Schematic {
+    area: usize,
+    numbers: Vec<PartNumber>,
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Schematic;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let schematic = Grid::parse(input, |_, b| Ok::<_, String>(b)).map_err(|err| err.locate(input))?;
+        let is_symbol = |b: u8| !matches!(b, b'0'..=b'9' | b'.');
+
+        let mut numbers = Vec::new();
+        let mut nearby_gears = Vec::new();
+
+        for (y, row) in input.lines().enumerate() {
+            let mut found_symbol_nearby = false;
+            let mut number_so_far: u64 = 0;
+
+            // Chain a fake cell at the end of the row so we don't forget the last number.
+            for (x, b) in row.bytes().chain(std::iter::once(b'.')).enumerate() {
+                if b.is_ascii_digit() {
+                    number_so_far = number_so_far
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add((b - b'0') as u64))
+                        .ok_or_else(|| Unexpected::new(&row[x..], "part number is too large").locate(input))?;
+
+                    for pos in schematic.neighbors8((y, x)) {
+                        if is_symbol(schematic[pos]) {
+                            found_symbol_nearby = true;
+                            if schematic[pos] == b'*' {
+                                nearby_gears.push(schematic.index_of(pos));
                             }
                         }
                     }
-                }
-            } else {
-                if found_symbol_nearby {
-                    part1 += number_so_far;
-                    if number_so_far != 0 {
+                } else {
+                    if found_symbol_nearby && number_so_far != 0 {
                         nearby_gears.sort_unstable();
                         nearby_gears.dedup();
-                        for (x, y) in nearby_gears.drain(..) {
-                            match &mut gears[y * width + x] {
-                                Some((l @ None, None)) => *l = Some(number_so_far),
-                                Some((Some(_), r @ None)) => *r = Some(number_so_far),
-                                otherwise => *otherwise = None,
-                            }
-                        }
+                        numbers.push(PartNumber {
+                            value: number_so_far,
+                            nearby_gears: std::mem::take(&mut nearby_gears),
+                        });
                     }
+                    found_symbol_nearby = false;
+                    number_so_far = 0;
                 }
-                found_symbol_nearby = false;
-                number_so_far = 0;
             }
         }
 
-        // don't forget the last number
-        if found_symbol_nearby {
-            part1 += number_so_far;
-
-            if number_so_far != 0 {
-                nearby_gears.sort_unstable();
-                nearby_gears.dedup();
-                for (x, y) in nearby_gears.drain(..) {
-                    match gears.get_mut(y * width + x).unwrap() {
-                        Some((l @ None, None)) => *l = Some(number_so_far),
-                        Some((Some(_), r @ None)) => *r = Some(number_so_far),
-                        otherwise => *otherwise = None,
-                    }
+        Ok(Schematic {
+            area: schematic.cells().len(),
+            numbers,
+        })
+    }
+
+    fn part1(schematic: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(schematic.numbers.iter().map(|number| number.value).sum())
+    }
+
+    fn part2(schematic: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        let mut gears = vec![Some((None, None)); schematic.area];
+        for number in &schematic.numbers {
+            for &gear in &number.nearby_gears {
+                match &mut gears[gear] {
+                    Some((l @ None, None)) => *l = Some(number.value),
+                    Some((Some(_), r @ None)) => *r = Some(number.value),
+                    otherwise => *otherwise = None,
                 }
             }
         }
+
+        Ok(gears
+            .into_iter()
+            .filter_map(|nearby| {
+                let (l, r) = nearby?;
+                Some(l? * r?)
+            })
+            .sum::<u64>())
     }
+}
 
-    let part2 = gears
-        .into_iter()
-        .filter_map(|nearby| {
-            let (l, r) = nearby?;
-            Some(l? * r?)
-        })
-        .sum::<u64>();
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
 
-    (part1, part2)
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("4361"), Some("467835"));
+    }
 }
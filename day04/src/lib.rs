@@ -1,64 +1,81 @@
 use std::fmt::Display;
 
-struct Card {
-    id: usize,
-    matching_numbers: usize,
-    amount_had: usize,
-    amount_to_process: usize,
-}
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
 
-fn parse_numbers(s: &'static str) -> impl Iterator<Item = u64> + '_ {
-    s.trim().split_ascii_whitespace().map(|s| s.parse().unwrap())
+fn parse_numbers(s: &str) -> Result<Vec<u64>, Unexpected<'_>> {
+    s.split_ascii_whitespace().map(str::try_parse).collect()
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
+pub struct Puzzle;
 
-    let mut cards = input
-        .lines()
-        .enumerate()
-        .map(|(id, line)| {
-            let (winners, candidates) = line.split_once(": ").unwrap().1.split_once(" | ").unwrap();
-            let winners = parse_numbers(winners).collect::<Vec<_>>();
-            let matching_numbers = parse_numbers(candidates).filter(|n| winners.contains(n)).count();
-            Card {
-                id: id + 1,
-                matching_numbers,
-                amount_had: 1,
-                amount_to_process: 1,
-            }
-        })
-        .collect::<Vec<_>>();
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
 
-    let part1 = cards
-        .iter()
-        .map(|&Card { matching_numbers, .. }| {
-            if matching_numbers == 0 {
-                0
-            } else {
-                1 << (matching_numbers - 1)
-            }
-        })
-        .sum::<usize>();
+    /// How many matching numbers each card has.
+    type Input<'a> = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .lines()
+            .map(|line| {
+                let (winners, candidates) = line.try_split_once(": ")?.1.try_split_once(" | ")?;
+                let winners = parse_numbers(winners)?;
+                Ok(parse_numbers(candidates)?
+                    .into_iter()
+                    .filter(|n| winners.contains(n))
+                    .count())
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|err: Unexpected| err.locate(input))
+    }
+
+    fn part1(cards: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(cards
+            .iter()
+            .map(|&matching_numbers| {
+                if matching_numbers == 0 {
+                    0
+                } else {
+                    1 << (matching_numbers - 1)
+                }
+            })
+            .sum::<usize>())
+    }
 
-    for idx in 0..cards.len() {
-        let Card {
-            amount_had: _,
-            amount_to_process,
-            matching_numbers,
-            id,
-        } = &mut cards[idx];
-        let id = *id;
-        let amount_to_add = *amount_to_process;
-        *amount_to_process = 0;
-        for won_id in id + 1..=id + *matching_numbers {
-            cards[won_id - 1].amount_had += amount_to_add;
-            cards[won_id - 1].amount_to_process += amount_to_add;
+    fn part2(cards: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        let mut amounts_had = vec![1; cards.len()];
+        for (idx, &matching_numbers) in cards.iter().enumerate() {
+            let amount_to_add = amounts_had[idx];
+            let won = idx + 1..(idx + 1 + matching_numbers).min(cards.len());
+            for amount_had in &mut amounts_had[won] {
+                *amount_had += amount_to_add;
+            }
         }
+
+        Ok(amounts_had.into_iter().sum::<usize>())
     }
+}
 
-    let part2 = cards.into_iter().map(|card| card.amount_had).sum::<usize>();
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
 
-    (part1, part2)
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("13"), Some("30"));
+    }
 }
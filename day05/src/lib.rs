@@ -1,35 +1,33 @@
 use std::fmt::Display;
 
-fn part1() -> impl Display {
-    let input = include_str!("input.txt");
-    let mut lines = input.lines();
-
-    let mut seeds = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
-        .split_ascii_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-    let _ = lines.next(); // burn empty line
-
-    let mut next_seeds: Vec<usize> = Vec::new();
-    while let Some(_) = lines.next() {
-        // while there is a next map
-        for next_line in lines.by_ref() {
-            if next_line.is_empty() {
-                break;
-            }
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_intervals::{Interval, IntervalSet};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MapEntry {
+    dest_start: u64,
+    src_start: u64,
+    range_len: u64,
+}
 
-            let mut it = next_line.split_ascii_whitespace().map(|s| s.parse::<usize>().unwrap());
-            let dest_start = it.next().unwrap();
-            let src_start = it.next().unwrap();
-            let range_len = it.next().unwrap();
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<Vec<MapEntry>>,
+}
 
+fn part1(almanac: &Almanac) -> u64 {
+    let mut seeds = almanac.seeds.clone();
+
+    let mut next_seeds: Vec<u64> = Vec::new();
+    for map in &almanac.maps {
+        for &MapEntry {
+            dest_start,
+            src_start,
+            range_len,
+        } in map
+        {
             seeds.retain(|&seed| {
-                if (src_start..src_start + range_len).contains(&seed) {
+                if Interval::from_len(src_start, range_len).contains(seed) {
                     next_seeds.push(seed - src_start + dest_start);
                     false
                 } else {
@@ -40,120 +38,151 @@ fn part1() -> impl Display {
         seeds.append(&mut next_seeds);
     }
 
-    seeds.into_iter().min().unwrap()
+    seeds.into_iter().min().expect("there should be seeds")
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct SeedRange {
-    start: usize,
-    len: usize,
-}
-
-const EMPTY_RANGE: SeedRange = SeedRange { start: 0, len: 0 };
-
-impl SeedRange {
-    fn intersection(&self, needle: &Self) -> (Self, Self, Self) {
-        let self_end = self.start + self.len;
-        let needle_end = needle.start + needle.len;
-
-        if self_end < needle.start || needle_end < self.start {
-            return (*self, EMPTY_RANGE, EMPTY_RANGE);
+fn part2(almanac: &Almanac) -> u64 {
+    let mut seeds = almanac
+        .seeds
+        .chunks_exact(2)
+        .map(|chunk| Interval::from_len(chunk[0], chunk[1]))
+        .collect::<IntervalSet>();
+
+    for map in &almanac.maps {
+        // Seeds only get moved by the first entry that covers them, so set aside the ones that already have been.
+        let mut moved = IntervalSet::new();
+        for &MapEntry {
+            dest_start,
+            src_start,
+            range_len,
+        } in map
+        {
+            let source = IntervalSet::from(Interval::from_len(src_start, range_len));
+            // Parsing made sure the destination fits, so this can't overflow.
+            let to_dest = |seed| seed - src_start + dest_start;
+            moved.extend(
+                seeds
+                    .intersection(&source)
+                    .iter()
+                    .map(|seeds| Interval::new(to_dest(seeds.start), to_dest(seeds.end))),
+            );
+            seeds = seeds.difference(&source);
         }
-
-        let common_start = self.start.max(needle.start);
-        let common_end = self_end.min(needle_end);
-        let common = SeedRange {
-            start: common_start,
-            len: common_end - common_start,
-        };
-        let left_start = self.start;
-        let left_end = common_start;
-        let left = SeedRange {
-            start: left_start,
-            len: left_end - left_start,
-        };
-        let right_start = common_end;
-        let right_end = self_end;
-        let right = SeedRange {
-            start: right_start,
-            len: right_end - right_start,
-        };
-        (left, common, right)
+        seeds = seeds.union(&moved);
     }
-}
 
-impl Display for SeedRange {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}..{}]", self.start, self.start + self.len)
-    }
+    seeds.min().expect("there should be seeds")
 }
 
-fn part2() -> impl Display {
-    let input = include_str!("input.txt");
+fn parse_almanac(input: &str) -> Result<Almanac, Unexpected<'_>> {
     let mut lines = input.lines();
 
-    let mut seeds = lines
-        .next()
-        .unwrap()
-        .split_once(": ")
-        .unwrap()
-        .1
+    let (_, seeds_text) = lines.next().unwrap_or_default().try_split_once(": ")?;
+    let seeds = seeds_text
         .split_ascii_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>()
+        .map(str::try_parse::<u64>)
+        .collect::<Result<Vec<_>, _>>()?;
+    if seeds.is_empty() || seeds.len() % 2 != 0 {
+        return Err(Unexpected::new(
+            seeds_text,
+            "expected seeds in pairs of a start and a length",
+        ));
+    }
+    if seeds
         .chunks_exact(2)
-        .map(|chunk| SeedRange {
-            start: chunk[0],
-            len: chunk[1],
-        })
-        .collect::<Vec<_>>();
+        .any(|pair| pair[1] == 0 || pair[0].checked_add(pair[1]).is_none())
+    {
+        return Err(Unexpected::new(
+            seeds_text,
+            "expected every range of seeds to be non-empty and fit in 64 bits",
+        ));
+    }
     let _ = lines.next(); // burn empty line
 
-    let mut next_seeds: Vec<SeedRange> = Vec::new();
-    let mut add_buffer = Vec::new();
+    let mut maps = Vec::new();
     while let Some(_header) = lines.next() {
         // while there is a next map
+        let mut map = Vec::new();
         for next_line in lines.by_ref() {
             if next_line.is_empty() {
                 break;
             }
 
-            let mut it = next_line.split_ascii_whitespace().map(|s| s.parse::<usize>().unwrap());
-            let dest_start = it.next().unwrap();
-            let src_start = it.next().unwrap();
-            let range_len = it.next().unwrap();
-
-            let dest_offset = dest_start as isize - src_start as isize;
-
-            let src_range = SeedRange {
-                start: src_start,
-                len: range_len,
+            let numbers = next_line
+                .split_ascii_whitespace()
+                .map(str::try_parse::<u64>)
+                .collect::<Result<Vec<_>, _>>()?;
+            let &[dest_start, src_start, range_len] = numbers.as_slice() else {
+                return Err(Unexpected::new(next_line, "expected exactly three numbers"));
             };
-
-            seeds.retain_mut(|seed| {
-                let (left, common, right) = seed.intersection(&src_range);
-                *seed = left;
-                if right.len != 0 {
-                    add_buffer.push(right);
-                }
-                if common.len != 0 {
-                    let new_seed = SeedRange {
-                        start: common.start.checked_add_signed(dest_offset).unwrap(),
-                        len: common.len,
-                    };
-                    next_seeds.push(new_seed);
-                }
-                left.len != 0
+            if src_start.checked_add(range_len).is_none() || dest_start.checked_add(range_len).is_none() {
+                return Err(Unexpected::new(next_line, "expected ranges that fit in 64 bits"));
+            }
+            map.push(MapEntry {
+                dest_start,
+                src_start,
+                range_len,
             });
-            seeds.append(&mut add_buffer);
         }
-        seeds.append(&mut next_seeds);
+        maps.push(map);
     }
 
-    seeds.into_iter().map(|sr| sr.start).min().unwrap()
+    Ok(Almanac { seeds, maps })
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Almanac;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_almanac(input).map_err(|err| err.locate(input))
+    }
+
+    fn part1(almanac: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(part1(almanac))
+    }
+
+    fn part2(almanac: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(part2(almanac))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
 }
 
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    (part1(), part2())
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("35"), Some("46"));
+    }
+
+    #[test]
+    fn rejects_missing_or_overflowing_ranges() {
+        for seeds in ["", "79", "79 0", "18446744073709551615 2"] {
+            let input = format!("seeds: {seeds}\n\nseed-to-soil map:\n50 98 2\n");
+            assert!(Puzzle::parse(&input).is_err(), "{seeds:?}");
+        }
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n18446744073709551615 98 2\n";
+        assert_eq!(
+            Puzzle::parse(input).err().unwrap().to_string(),
+            "line 4, column 1: expected ranges that fit in 64 bits"
+        );
+    }
 }
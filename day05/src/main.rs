@@ -1,6 +1,8 @@
-fn main() {
-    color_eyre::install().unwrap();
-    let (part1, part2) = day05::solve();
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+    let (part1, part2) = day05::solve()?;
+    aoc_common::bounds::warn_if_ruled_out("day05", &part1, &part2);
     println!("{part1}");
     println!("{part2}");
+    Ok(())
 }
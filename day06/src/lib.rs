@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+
 fn calculate_answer((time, distance): (f64, f64)) -> f64 {
     // assuming I hold the button for v seconds, I'll then travel v * time_remaining.
     // let's call t the max time alloted and d the distance to travel, we want
@@ -18,43 +20,74 @@ fn calculate_answer((time, distance): (f64, f64)) -> f64 {
     high - low
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    /// The races as written, then the single race you get by ignoring the spaces.
+    type Input<'a> = (Vec<(f64, f64)>, (f64, f64));
+    type Part1 = f64;
+    type Part2 = f64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let mut lines = input.lines();
+        let mut next_line = |label| {
+            let line = lines
+                .next()
+                .ok_or_else(|| Unexpected::new("", format!("expected a {label:?} line")))?;
+            line.try_split_once(":").map(|(_, values)| values)
+        };
+        let (times, distances) = next_line("Time")
+            .and_then(|times| Ok((times, next_line("Distance")?)))
+            .map_err(|err| err.locate(input))?;
+
+        let parse_races = |values: &str| -> Result<Vec<f64>, ParseError> {
+            values
+                .split_whitespace()
+                .map(|x| x.try_parse::<f64>().map_err(|err| err.locate(input)))
+                .collect()
+        };
+        let races = parse_races(times)?.into_iter().zip(parse_races(distances)?).collect();
+
+        let parse_race = |values: &str| {
+            values
+                .replace(' ', "")
+                .parse::<f64>()
+                .map_err(|err| Unexpected::new(values, format!("invalid number {values:?}: {err}")).locate(input))
+        };
+        let race = (parse_race(times)?, parse_race(distances)?);
+
+        Ok((races, race))
+    }
+
+    fn part1((races, _): &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(races.iter().copied().map(calculate_answer).product::<f64>())
+    }
+
+    fn part2(&(_, race): &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(calculate_answer(race))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let mut input = include_str!("input.txt").lines();
-    let times = input
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|x| x.parse::<f64>().unwrap());
-    let distances = input
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|x| x.parse::<f64>().unwrap());
-    let part1 = times.zip(distances).map(calculate_answer).product::<f64>();
-
-    let mut input = include_str!("input.txt").lines();
-    let time = input
-        .next()
-        .unwrap()
-        .split_once(':')
-        .unwrap()
-        .1
-        .replace(' ', "")
-        .parse::<f64>()
-        .unwrap();
-    let distance = input
-        .next()
-        .unwrap()
-        .split_once(':')
-        .unwrap()
-        .1
-        .replace(' ', "")
-        .parse::<f64>()
-        .unwrap();
-    let part2 = calculate_answer((time, distance));
-
-    (part1, part2)
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("288"), Some("71503"));
+    }
 }
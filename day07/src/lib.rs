@@ -1,12 +1,68 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+
 mod part1;
 mod part2;
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    /// Each hand, still as text since the two parts rank cards differently, and its bid.
+    type Input<'a> = Vec<(&'a str, usize)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .lines()
+            .map(|line| {
+                let (hand, bid) = line.try_split_once(" ")?;
+                if hand.len() != 5 || !hand.bytes().all(|card| b"23456789TJQKA".contains(&card)) {
+                    return Err(Unexpected::new(hand, format!("invalid hand {hand:?}")));
+                }
+                Ok((hand, bid.try_parse::<usize>()?))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(hands: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(part1::solve(hands))
+    }
+
+    fn part2(hands: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(part2::solve(hands))
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let hands = Self::parse(input)?;
+        Self::check_assumptions(&hands)?;
+        let (part1, part2) = aoc_common::join(|| Self::part1(&hands), || Self::part2(&hands));
+        Ok((part1?, part2?))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    rayon::join(
-        || part1::solve(include_str!("input.txt")),
-        || part2::solve(include_str!("input.txt")),
-    )
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("6440"), Some("5905"));
+    }
 }
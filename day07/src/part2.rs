@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, fmt::Display};
+use std::cmp::Ordering;
 
 const CARD_TYPES: usize = 13;
 const CARDS: [u8; CARD_TYPES] = [
@@ -100,15 +100,10 @@ impl Ord for Hand {
     }
 }
 
-pub(super) fn solve(input: &str) -> impl Display {
-    let mut hands = input
-        .lines()
-        .map(|line| {
-            let (hand, bid) = line.split_once(' ').unwrap();
-            let hand = Hand::new(hand);
-            let bid = bid.parse::<usize>().unwrap();
-            (hand, bid)
-        })
+pub(super) fn solve(hands: &[(&str, usize)]) -> usize {
+    let mut hands = hands
+        .iter()
+        .map(|&(hand, bid)| (Hand::new(hand), bid))
         .collect::<Vec<_>>();
 
     hands.sort_unstable_by_key(|&(hand, _)| hand);
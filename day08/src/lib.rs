@@ -1,7 +1,10 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_math::lcm;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use rustc_hash::FxHashMap as HashMap;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
 type Node = u32;
 type Map = HashMap<Node, Branch>;
@@ -11,17 +14,28 @@ const fn convert_id(s: &str) -> Node {
     Node::from_be_bytes([0, b[0], b[1], b[2]])
 }
 
+fn parse_id(s: &str) -> Result<Node, Unexpected<'_>> {
+    if s.len() != 3 {
+        return Err(Unexpected::new(s, format!("expected a three letter node, got {s:?}")));
+    }
+    Ok(convert_id(s))
+}
+
+fn node_name(node: Node) -> String {
+    node.to_be_bytes()[1..].iter().map(|&b| char::from(b)).collect()
+}
+
 const P1_START: Node = convert_id("AAA");
 const P1_GOAL: Node = convert_id("ZZZ");
 
 #[derive(Clone, Copy, Debug)]
-struct Branch {
+pub struct Branch {
     left: Node,
     right: Node,
 }
 
 #[derive(Clone, Copy, PartialEq)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
 }
@@ -35,48 +49,41 @@ impl Display for Direction {
     }
 }
 
-fn part1(map: &Map, mut directions_to_take: impl Iterator<Item = Direction>) -> usize {
-    let mut current = P1_START;
-    for n in 0.. {
-        if current == P1_GOAL {
-            return n;
+/// Walk from `node` until reaching a node `is_goal` accepts, returning how many steps it took, or `None` if the walk
+/// goes around in circles without ever getting there.
+fn walk_to(map: &Map, directions: &[Direction], mut node: Node, is_goal: impl Fn(Node) -> bool) -> Option<usize> {
+    // Past this many steps, we must have been at the same node at the same point in the directions twice.
+    let limit = map.len() * directions.len();
+    for (steps, direction) in directions.iter().cycle().take(limit).enumerate() {
+        if is_goal(node) {
+            return Some(steps);
         }
-
-        let branch = map.get(&current).unwrap();
-        current = match directions_to_take.next().unwrap() {
+        let branch = map[&node];
+        node = match direction {
             Direction::Left => branch.left,
             Direction::Right => branch.right,
         };
     }
+    None
+}
 
-    unreachable!();
+fn is_start(node: Node) -> bool {
+    node & 0xFF == b'A' as Node
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    if b == 0 {
-        a
-    } else {
-        gcd(b, a % b)
-    }
+fn is_end(node: Node) -> bool {
+    node & 0xFF == b'Z' as Node
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    if a > b {
-        (a / gcd(a, b)) * b
-    } else {
-        (b / gcd(a, b)) * a
-    }
+fn part1(map: &Map, directions: &[Direction]) -> Result<usize, BrokenAssumption> {
+    map.contains_key(&P1_START)
+        .then(|| walk_to(map, directions, P1_START, |node| node == P1_GOAL))
+        .flatten()
+        .ok_or_else(|| BrokenAssumption::new("AAA should lead to ZZZ"))
 }
 
-fn part2<I>(map: &Map, directions_to_take: I) -> usize
-where
-    I: Iterator<Item = Direction> + Clone + Sync,
-{
-    let starting_nodes = map
-        .keys()
-        .filter(|&&node| node & 0xFF == b'A' as Node)
-        .copied()
-        .collect::<Vec<_>>();
+fn part2(map: &Map, directions: &[Direction]) -> Result<u64, BrokenAssumption> {
+    let starting_nodes = map.keys().copied().filter(|&node| is_start(node)).collect::<Vec<_>>();
 
     // Each node is independent from the others, so we can figure out the number of steps to reach
     // the goal for each node, and then find the least common multiple of the cycle length of each.
@@ -85,54 +92,183 @@ where
     //
     // The input seems to then be designed so that the cycle length is the same as the amount of
     // steps it takes you to reach the goal the first time!
-    let cycle_lengths = starting_nodes.into_par_iter().map(|mut node| {
-        let mut directions_to_take = directions_to_take.clone();
-        let mut steps = 0;
-        while node & 0xFF != b'Z' as Node {
-            steps += 1;
-            let direction = directions_to_take.next().unwrap();
-            let branch = map.get(&node).unwrap();
-            node = match direction {
-                Direction::Left => branch.left,
-                Direction::Right => branch.right,
-            };
-        }
-        steps
-    });
+    #[cfg(feature = "parallel")]
+    let starting_nodes = starting_nodes.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let starting_nodes = starting_nodes.into_iter();
+    let cycle_lengths = starting_nodes
+        .map(|node| {
+            walk_to(map, directions, node, is_end)
+                .map(|steps| steps as u64)
+                .ok_or_else(|| {
+                    BrokenAssumption::new(format!(
+                        "the ghost starting at {} should reach an end node",
+                        node_name(node)
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Find the least common multiple of the cycle lengths.
-    cycle_lengths.reduce(|| 1, lcm)
+    Ok(cycle_lengths.into_iter().fold(1, lcm))
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
+fn check_network(map: &Map) -> Result<(), BrokenAssumption> {
+    for (&node, branch) in map {
+        if let Some(&missing) = [branch.left, branch.right].iter().find(|n| !map.contains_key(n)) {
+            return Err(BrokenAssumption::new(format!(
+                "{} should lead to nodes in the network, but {} isn't",
+                node_name(node),
+                node_name(missing)
+            )));
+        }
+    }
+
+    // What makes the ghosts go around a cycle as long as the way to it: each start node has an end node leading to
+    // the same nodes, so the way on from the end is much like setting off from the start again.
+    let branches = |branch: &Branch| {
+        let mut branches = [branch.left, branch.right];
+        branches.sort_unstable();
+        branches
+    };
+    let end_branches = map
+        .iter()
+        .filter(|(&node, _)| is_end(node))
+        .map(|(_, branch)| branches(branch))
+        .collect::<HashSet<_>>();
+    for (&start, branch) in map.iter().filter(|(&node, _)| is_start(node)) {
+        if !end_branches.contains(&branches(branch)) {
+            return Err(BrokenAssumption::new(format!(
+                "the ghost starting at {} should have an end node leading to the same nodes as its start",
+                node_name(start)
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_network(input: &str) -> Result<(Vec<Direction>, Map), Unexpected<'_>> {
     let mut map = Map::default();
-    let mut input = include_str!("input.txt").lines();
-    let directions_to_take = input
-        .next()
-        .unwrap()
-        .chars()
-        .map(|ch| match ch {
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            _ => unreachable!(),
+    let mut lines = input.lines();
+    let first_line = lines.next().unwrap_or_default();
+    if first_line.is_empty() {
+        return Err(Unexpected::new(first_line, "expected a list of directions"));
+    }
+    let directions = first_line
+        .char_indices()
+        .map(|(idx, ch)| match ch {
+            'L' => Ok(Direction::Left),
+            'R' => Ok(Direction::Right),
+            _ => Err(Unexpected::new(&first_line[idx..], format!("unknown direction {ch:?}"))),
         })
-        .cycle();
-    let _ = input.next();
-    for line in input {
-        let (node, branches) = line.split_once(" = ").unwrap();
-        let (left, right) = branches.trim_matches(&['(', ')'][..]).split_once(", ").unwrap();
-        map.insert(
-            convert_id(node),
-            Branch {
-                left: convert_id(left),
-                right: convert_id(right),
-            },
-        );
-    }
-
-    rayon::join(
-        || part1(&map, directions_to_take.clone()),
-        || part2(&map, directions_to_take.clone()),
-    )
+        .collect::<Result<_, _>>()?;
+    let _ = lines.next();
+    for line in lines {
+        let (node, branches) = line.try_split_once(" = ")?;
+        let (left, right) = branches.trim_matches(&['(', ')'][..]).try_split_once(", ")?;
+        let branch = Branch {
+            left: parse_id(left)?,
+            right: parse_id(right)?,
+        };
+        if map.insert(parse_id(node)?, branch).is_some() {
+            return Err(Unexpected::new(node, format!("node {node} is defined twice")));
+        }
+    }
+    Ok((directions, map))
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = (Vec<Direction>, Map);
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_network(input).map_err(|err| err.locate(input))
+    }
+
+    fn part1((directions, map): &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        part1(map, directions)
+    }
+
+    fn part2((directions, map): &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        part2(map, directions)
+    }
+
+    fn check_assumptions((_, map): &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        check_network(map)
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let input = Self::parse(input)?;
+        Self::check_assumptions(&input)?;
+        let (part1, part2) = aoc_common::join(|| Self::part1(&input), || Self::part2(&input));
+        Ok((part1?, part2?))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        check_example::<Puzzle>(include_str!("sample_input_2.txt"), Some("2"), None);
+    }
+
+    #[test]
+    fn part1_repeating_example() {
+        check_example::<Puzzle>(include_str!("sample_input_3.txt"), Some("6"), None);
+    }
+
+    #[test]
+    fn part2_example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), None, Some("6"));
+    }
+
+    #[test]
+    fn rejects_duplicate_nodes() {
+        let Err(err) = Puzzle::parse("L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\nAAA = (AAA, AAA)") else {
+            panic!("a node defined twice should be rejected");
+        };
+        assert_eq!(err.to_string(), "line 5, column 1: node AAA is defined twice");
+    }
+
+    #[test]
+    fn ghosts_off_their_cycle_break_assumptions() {
+        let network = Puzzle::parse(
+            "LR
+
+AAA = (BBB, BBB)
+BBB = (ZZZ, ZZZ)
+ZZZ = (BBB, BBB)
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+        )
+        .unwrap();
+        let err = Puzzle::check_assumptions(&network).unwrap_err();
+        assert!(err.reason.contains("22A"), "{err}");
+    }
 }
@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 fn diff(x: &mut Vec<i32>) {
@@ -11,25 +13,112 @@ fn diff(x: &mut Vec<i32>) {
     }
 }
 
+/// Extrapolate both the next and the previous value of the report, or `None` if it runs out of differences before
+/// they all come to zero.
+fn extrapolate(report: &[i32]) -> Option<(i32, i32)> {
+    let mut report = report.to_vec();
+    let mut p1 = *report.last()?;
+    let mut p2 = *report.first()?;
+    let mut negated = true;
+    while !report.iter().all(|&n| n == 0) {
+        diff(&mut report);
+        let f = *report.first()?;
+        p1 += report.last()?;
+        p2 += if negated { -f } else { f };
+        negated = !negated;
+    }
+    Some((p1, p2))
+}
+
+fn ran_out() -> BrokenAssumption {
+    BrokenAssumption::new("every history should have enough values for its differences to come to zero")
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Vec<Vec<i32>>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .lines()
+            .map(|line| line.split(' ').map(str::try_parse).collect())
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(reports: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        #[cfg(feature = "parallel")]
+        let reports = reports.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let reports = reports.iter();
+        reports
+            .map(|report| extrapolate(report).map(|extrapolated| extrapolated.0))
+            .sum::<Option<_>>()
+            .ok_or_else(ran_out)
+    }
+
+    fn part2(reports: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        #[cfg(feature = "parallel")]
+        let reports = reports.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let reports = reports.iter();
+        reports
+            .map(|report| extrapolate(report).map(|extrapolated| extrapolated.1))
+            .sum::<Option<_>>()
+            .ok_or_else(ran_out)
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let reports = Self::parse(input)?;
+        Self::check_assumptions(&reports)?;
+        #[cfg(feature = "parallel")]
+        let reports = reports.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let reports = reports.into_iter();
+        let extrapolated = reports.map(|report| extrapolate(&report));
+        let add = |a: Option<(i32, i32)>, b: Option<(i32, i32)>| {
+            let ((p11, p21), (p12, p22)) = (a?, b?);
+            Some((p11 + p12, p21 + p22))
+        };
+        #[cfg(feature = "parallel")]
+        let total = extrapolated.reduce(|| Some((0, 0)), add);
+        #[cfg(not(feature = "parallel"))]
+        let total = extrapolated.fold(Some((0, 0)), add);
+        Ok(total.ok_or_else(ran_out)?)
+    }
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    include_str!("input.txt")
-        .lines()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(|line| {
-            let mut report: Vec<i32> = line.split(' ').map(|n| n.parse().unwrap()).collect();
-            let mut p1 = *report.last().unwrap();
-            let mut p2 = *report.first().unwrap();
-            let mut negated = true;
-            while !report.iter().all(|&n| n == 0) {
-                diff(&mut report);
-                let f = *report.first().unwrap();
-                p1 += report.last().unwrap();
-                p2 += if negated { -f } else { f };
-                negated = !negated;
-            }
-            (p1, p2)
-        })
-        .reduce(|| (0, 0), |(p11, p21), (p12, p22)| (p11 + p12, p21 + p22))
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("114"), Some("2"));
+    }
+
+    #[test]
+    fn short_histories_break_assumptions() {
+        let reports = Puzzle::parse("5").unwrap();
+        assert_eq!(Puzzle::part1(&reports), Err(ran_out()));
+        assert!(solve_input("0 3 6\n5\n").is_err());
+        assert!(solve_input("0 3 6\n0\n").is_ok());
+    }
 }
@@ -1,10 +1,11 @@
 use std::fmt::Display;
 
-use ::tap::Pipe as _;
-use grid::Grid;
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+use aoc_geometry::Polygon;
+use aoc_grid::{Direction, Grid};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Pipe {
+pub enum Pipe {
     NS,
     EW,
     NE,
@@ -26,97 +27,394 @@ impl Display for Pipe {
     }
 }
 
-const S_REPLACEMENT: Pipe = Pipe::EW;
+const NORTH: (isize, isize) = Direction::Up.offset();
+const SOUTH: (isize, isize) = Direction::Down.offset();
+const WEST: (isize, isize) = Direction::Left.offset();
+const EAST: (isize, isize) = Direction::Right.offset();
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let mut start_pos = None;
-    let input = include_str!("input.txt");
-    let columns = input.lines().next().unwrap().len();
-    let map = input
-        .bytes()
-        .filter(|b| *b != b'\n')
-        .enumerate()
-        .map(|(idx, b)| match b {
-            b'|' => Some(Pipe::NS),
-            b'-' => Some(Pipe::EW),
-            b'L' => Some(Pipe::NE),
-            b'J' => Some(Pipe::NW),
-            b'7' => Some(Pipe::SW),
-            b'F' => Some(Pipe::SE),
-            b'S' => {
-                let y = idx / columns;
-                let x = idx % columns;
-                start_pos = Some((y, x));
-                Some(S_REPLACEMENT)
-            }
-            b'.' => None,
-            _ => unreachable!("{b:?}"),
-        })
-        .pipe(|iter| Grid::from_vec(iter.collect(), columns));
+impl Pipe {
+    const ALL: [Pipe; 6] = [Pipe::NS, Pipe::EW, Pipe::NE, Pipe::NW, Pipe::SW, Pipe::SE];
+
+    /// The two directions, as `(dy, dx)`, this pipe connects.
+    fn ends(self) -> [(isize, isize); 2] {
+        match self {
+            Pipe::NS => [NORTH, SOUTH],
+            Pipe::EW => [WEST, EAST],
+            Pipe::NE => [NORTH, EAST],
+            Pipe::NW => [NORTH, WEST],
+            Pipe::SW => [SOUTH, WEST],
+            Pipe::SE => [SOUTH, EAST],
+        }
+    }
+}
+
+type Map = Grid<Option<Pipe>>;
 
-    let start_pos = start_pos.unwrap();
+/// Walk around the loop, taking the middle of each tile on it, as `(x, y)`, as a vertex of a polygon.
+fn walk_loop(map: &Map, start_pos: (usize, usize)) -> Polygon {
     let mut pos = start_pos;
-    let mut dir: (isize, isize) = match S_REPLACEMENT {
-        Pipe::EW => (0, 1),
-        _ => unimplemented!(),
+    let [mut dir, _] = map[start_pos].expect("start should be on the loop").ends();
+    let mut vertices = Vec::new();
+    loop {
+        vertices.push((pos.1 as i128, pos.0 as i128));
+        pos = map.offset(pos, dir).expect("should not leave the map");
+        if pos == start_pos {
+            break;
+        }
+        dir = match map[pos].expect("should not leave loop!").ends() {
+            [came_from, towards] | [towards, came_from] if came_from == (-dir.0, -dir.1) => towards,
+            _ => unreachable!("the pipes should connect"),
+        };
+    }
+    Polygon::new(vertices)
+}
+
+/// Follow the pipes out of the starting tile, checking that they lead back into it without any loose ends.
+fn is_closed_loop(map: &Map, start_pos: (usize, usize)) -> bool {
+    let Some(start_pipe) = map[start_pos] else {
+        return false;
     };
+    let mut pos = start_pos;
+    let [mut dir, _] = start_pipe.ends();
+    for _ in 0..map.cells().len() {
+        let Some(next) = map.offset(pos, dir) else {
+            return false;
+        };
+        let Some(&Some(pipe)) = map.get(next) else {
+            return false;
+        };
+        pos = next;
+        dir = match pipe.ends() {
+            [came_from, towards] | [towards, came_from] if came_from == (-dir.0, -dir.1) => towards,
+            _ => return false,
+        };
+        if pos == start_pos {
+            return true;
+        }
+    }
+    false
+}
 
-    let mut loop_len = 0;
-    let mut area = 0isize;
+// The farthest tile is halfway around the loop, and Pick's theorem gives us the tiles it encloses.
+fn loop_answers(pipes: &Polygon) -> (i128, i128) {
+    (pipes.boundary_points() / 2, pipes.interior_points())
+}
 
-    loop {
-        loop_len += 1;
-        area -= dir.0 * pos.1 as isize; // Green's theorem
-
-        match map[pos].expect("should not leave loop!") {
-            // Straight segments just leave the direction as is
-            Pipe::NS | Pipe::EW => {}
-
-            // Curved segments change the direction
-            Pipe::NE => {
-                // ╚
-                dir = match dir {
-                    (1, 0) => (0, 1),   // down -> right
-                    (0, -1) => (-1, 0), // left -> up
-                    _ => unreachable!(),
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = (Map, (usize, usize));
+    type Part1 = i128;
+    type Part2 = i128;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let mut start_pos = None;
+        let mut map = Grid::parse(input, |pos, b| {
+            Ok(match b {
+                b'|' => Some(Pipe::NS),
+                b'-' => Some(Pipe::EW),
+                b'L' => Some(Pipe::NE),
+                b'J' => Some(Pipe::NW),
+                b'7' => Some(Pipe::SW),
+                b'F' => Some(Pipe::SE),
+                b'S' => {
+                    start_pos = Some(pos);
+                    None
                 }
-            }
-            Pipe::NW => {
-                // ╝
-                dir = match dir {
-                    (0, 1) => (-1, 0), // right -> up
-                    (1, 0) => (0, -1), // down -> left
-                    _ => unreachable!("{dir:?}"),
+                b'.' => None,
+                _ => return Err("expected a pipe, the ground or the starting tile"),
+            })
+        })
+        .map_err(|err| err.locate(input))?;
+
+        let start_pos = start_pos.ok_or_else(|| Unexpected::new("", "no starting tile 'S'").locate(input))?;
+
+        // Figure out which pipe is hiding under the starting tile from the pipes around it that connect to it.
+        let (y, x) = start_pos;
+        let connects = |(dy, dx): (isize, isize)| {
+            let pipe = map[map.offset(start_pos, (dy, dx))?]?;
+            Some(pipe.ends().contains(&(-dy, -dx)))
+        };
+        let connected = [NORTH, SOUTH, WEST, EAST]
+            .into_iter()
+            .filter(|&dir| connects(dir).unwrap_or(false))
+            .collect::<Vec<_>>();
+        let start_pipe = Pipe::ALL.into_iter().find(|pipe| pipe.ends().as_slice() == connected);
+        let Some(start_pipe) = start_pipe else {
+            let line = input.lines().nth(y).unwrap_or_default();
+            let reason = "the starting tile should connect to exactly two pipes";
+            return Err(Unexpected::new(&line[x..], reason).locate(input));
+        };
+        map[start_pos] = Some(start_pipe);
+
+        Ok((map, start_pos))
+    }
+
+    fn part1((map, start_pos): &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(loop_answers(&walk_loop(map, *start_pos)).0)
+    }
+
+    fn part2((map, start_pos): &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(loop_answers(&walk_loop(map, *start_pos)).1)
+    }
+
+    fn check_assumptions((map, start_pos): &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        if is_closed_loop(map, *start_pos) {
+            Ok(())
+        } else {
+            Err(BrokenAssumption::new(
+                "the pipes out of the starting tile should form a loop",
+            ))
+        }
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let input = Self::parse(input)?;
+        Self::check_assumptions(&input)?;
+        let (map, start_pos) = input;
+        Ok(loop_answers(&walk_loop(&map, start_pos)))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn square_loop() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("4"), Some("1"));
+    }
+
+    #[test]
+    fn square_loop_with_junk() {
+        check_example::<Puzzle>(include_str!("sample_input_2.txt"), Some("4"), None);
+    }
+
+    #[test]
+    fn complex_loop() {
+        check_example::<Puzzle>(include_str!("sample_input_3.txt"), Some("8"), None);
+    }
+
+    #[test]
+    fn complex_loop_with_junk() {
+        check_example::<Puzzle>(include_str!("sample_input_4.txt"), Some("8"), None);
+    }
+
+    #[test]
+    fn enclosed_tiles() {
+        check_example::<Puzzle>(include_str!("sample_input_5.txt"), None, Some("4"));
+    }
+
+    #[test]
+    fn larger_enclosed_tiles() {
+        check_example::<Puzzle>(include_str!("sample_input_6.txt"), None, Some("8"));
+    }
+
+    #[test]
+    fn enclosed_tiles_with_junk() {
+        check_example::<Puzzle>(include_str!("sample_input_7.txt"), None, Some("10"));
+    }
+
+    #[test]
+    fn broken_loop_breaks_assumptions() {
+        let input = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&input), Ok(()));
+
+        let input = Puzzle::parse("S-7\n|.|\nL-.").unwrap();
+        assert!(Puzzle::check_assumptions(&input).is_err());
+    }
+
+    const MAX_HEIGHT: usize = 8;
+
+    /// Which directions a tile connects to. The starting tile may connect to anything.
+    fn connections(tile: u8) -> &'static [(isize, isize)] {
+        match tile {
+            b'|' => &[NORTH, SOUTH],
+            b'-' => &[WEST, EAST],
+            b'L' => &[NORTH, EAST],
+            b'J' => &[NORTH, WEST],
+            b'7' => &[SOUTH, WEST],
+            b'F' => &[SOUTH, EAST],
+            b'S' => &[NORTH, SOUTH, WEST, EAST],
+            _ => &[],
+        }
+    }
+
+    /// Flood the pipes connected to the starting tile to find how far the loop goes, then blow the map up so that
+    /// every tile is 3x3 and flood the outside of the loop, squeezing between pipes and all, to find what it encloses.
+    fn naive_solve(input: &str) -> (usize, usize) {
+        let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (height, width) = (tiles.len(), tiles[0].len());
+        let tiles = &tiles;
+        let neighbors = |(y, x): (usize, usize)| {
+            connections(tiles[y][x]).iter().filter_map(move |&(dy, dx)| {
+                let (ny, nx) = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+                let connected = ny < height && nx < width && connections(tiles[ny][nx]).contains(&(-dy, -dx));
+                connected.then_some(((ny, nx), (dy, dx)))
+            })
+        };
+
+        let start = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .find(|&(y, x)| tiles[y][x] == b'S')
+            .unwrap();
+        let mut distances = vec![vec![None; width]; height];
+        distances[start.0][start.1] = Some(0);
+        let mut queue = std::collections::VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[pos.0][pos.1].unwrap();
+            for ((ny, nx), _) in neighbors(pos) {
+                if distances[ny][nx].is_none() {
+                    distances[ny][nx] = Some(distance + 1);
+                    queue.push_back((ny, nx));
                 }
             }
-            Pipe::SW => {
-                // ╗
-                dir = match dir {
-                    (0, 1) => (1, 0),   // right -> down
-                    (-1, 0) => (0, -1), // up -> left
-                    _ => unreachable!(),
+        }
+        let part1 = distances.iter().flatten().flatten().copied().max().unwrap();
+
+        // Leave a border around the map so the outside is all connected.
+        let mut walls = vec![vec![false; 3 * width + 2]; 3 * height + 2];
+        for (y, row) in distances.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                if distance.is_none() {
+                    continue;
                 }
-            }
-            Pipe::SE => {
-                // ╔
-                dir = match dir {
-                    (-1, 0) => (0, 1), // up -> right
-                    (0, -1) => (1, 0), // right -> down
-                    _ => unreachable!(),
+                let (cy, cx) = (3 * y + 2, 3 * x + 2);
+                walls[cy][cx] = true;
+                for (_, (dy, dx)) in neighbors((y, x)) {
+                    walls[cy.wrapping_add_signed(dy)][cx.wrapping_add_signed(dx)] = true;
                 }
             }
         }
+        let mut outside = vec![vec![false; 3 * width + 2]; 3 * height + 2];
+        let mut stack = vec![(0, 0)];
+        while let Some((y, x)) = stack.pop() {
+            if y >= walls.len() || x >= walls[0].len() || walls[y][x] || outside[y][x] {
+                continue;
+            }
+            outside[y][x] = true;
+            stack.extend([(y + 1, x), (y.wrapping_sub(1), x), (y, x + 1), (y, x.wrapping_sub(1))]);
+        }
+        let part2 = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .filter(|&(y, x)| distances[y][x].is_none() && !outside[3 * y + 2][3 * x + 2])
+            .count();
 
-        pos.0 = pos.0.checked_add_signed(dir.0).unwrap();
-        pos.1 = pos.1.checked_add_signed(dir.1).unwrap();
+        (part1, part2)
+    }
 
-        if pos == start_pos {
-            break;
-        }
+    /// A map whose loop traces the outline of side-by-side columns of tiles, each of them overlapping the next, with
+    /// junk pipes strewn everywhere else.
+    fn pipe_maze() -> impl Strategy<Value = String> {
+        let column = (1..4usize, 0..MAX_HEIGHT, 0..MAX_HEIGHT);
+        let junk = proptest::collection::vec(
+            prop_oneof![Just(b'.'), proptest::sample::select(b"|-LJ7F".to_vec())],
+            1024,
+        );
+        (
+            proptest::collection::vec(column, 1..8),
+            junk,
+            any::<prop::sample::Index>(),
+        )
+            .prop_map(|(columns, junk, start)| {
+                // Turn the random numbers into `(width, top, bottom)` columns that overlap their predecessors.
+                let mut spans: Vec<(usize, usize, usize)> = Vec::with_capacity(columns.len());
+                for (width, a, b) in columns {
+                    let (top, bottom) = match spans.last() {
+                        None => (a.min(b), a.max(b) + 1),
+                        Some(&(_, prev_top, prev_bottom)) => {
+                            let top = a % prev_bottom;
+                            (top, (top.max(prev_top) + 1 + b).min(MAX_HEIGHT))
+                        }
+                    };
+                    spans.push((width, top, bottom));
+                }
+
+                // Walk along the top from left to right, then along the bottom from right to left.
+                let origin = (spans[0].2, 0);
+                let mut path = vec![origin];
+                let mut go_to = |(y, x): (usize, usize)| {
+                    while *path.last().unwrap() != (y, x) {
+                        let (py, px) = *path.last().unwrap();
+                        path.push(if py != y {
+                            (if y > py { py + 1 } else { py - 1 }, px)
+                        } else {
+                            (py, if x > px { px + 1 } else { px - 1 })
+                        });
+                    }
+                };
+                let mut x = 0;
+                for &(width, top, _) in &spans {
+                    go_to((top, x));
+                    x += width;
+                    go_to((top, x));
+                }
+                for &(width, _, bottom) in spans.iter().rev() {
+                    go_to((bottom, x));
+                    x -= width;
+                    go_to((bottom, x));
+                }
+                go_to(origin);
+                path.pop();
+
+                let width = spans.iter().map(|&(width, _, _)| width).sum::<usize>() + 1;
+                let mut tiles = junk
+                    .chunks(width)
+                    .take(MAX_HEIGHT + 1)
+                    .map(<[u8]>::to_vec)
+                    .collect::<Vec<_>>();
+                for (idx, &(y, x)) in path.iter().enumerate() {
+                    let towards = |(ny, nx): (usize, usize)| (ny as isize - y as isize, nx as isize - x as isize);
+                    let ends = [
+                        towards(path[(idx + path.len() - 1) % path.len()]),
+                        towards(path[(idx + 1) % path.len()]),
+                    ];
+                    tiles[y][x] = *b"|-LJ7F"
+                        .iter()
+                        .find(|&&tile| ends.iter().all(|end| connections(tile).contains(end)))
+                        .unwrap();
+                }
+
+                // Junk right next to the starting tile could make it ambiguous which pipe it's hiding.
+                let (sy, sx) = path[start.index(path.len())];
+                for (dy, dx) in [NORTH, SOUTH, WEST, EAST] {
+                    let (Some(ny), Some(nx)) = (sy.checked_add_signed(dy), sx.checked_add_signed(dx)) else {
+                        continue;
+                    };
+                    if ny < tiles.len() && nx < width && !path.contains(&(ny, nx)) {
+                        tiles[ny][nx] = b'.';
+                    }
+                }
+                tiles[sy][sx] = b'S';
+
+                tiles
+                    .into_iter()
+                    .map(|row| String::from_utf8(row).unwrap())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
     }
 
-    let part1 = loop_len / 2;
-    let part2 = area.abs() - part1 + 1;
-    (part1, part2)
+    proptest! {
+        #[test]
+        fn matches_naive_solution(input in pipe_maze()) {
+            let (part1, part2) = Puzzle::solve(&input).unwrap();
+            prop_assert_eq!((part1 as usize, part2 as usize), naive_solve(&input));
+        }
+    }
 }
@@ -1,27 +1,30 @@
 use std::fmt::Display;
-fn do_solve(padding: usize) -> usize {
-    // Load the original space.
-    let space = (include_str!("input.txt"))
-        .lines()
-        .map(|row| row.chars().map(|c| c == '#').collect::<Vec<_>>())
-        .collect::<Vec<_>>();
+
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution};
+use aoc_grid::Grid;
+
+type Space = Grid<bool>;
+
+/// Sum the distances between every pair of galaxies once each empty row and column has grown to `expansion` of them.
+fn do_solve(space: &Space, expansion: usize) -> usize {
+    let padding = expansion - 1;
 
     // Find which rows need to be expanded.
     let mut empty_row_indices = space
-        .iter()
+        .rows()
         .enumerate()
         .filter(|(_, row)| row.iter().all(|&c| !c))
         .map(|(y, _)| y)
         .peekable();
-    let empty_col_indices = (0..space[0].len())
-        .filter(|&x| space.iter().all(|row| !row[x]))
+    let empty_col_indices = (0..space.width())
+        .filter(|&x| space.column(x).all(|&c| !c))
         .collect::<Vec<_>>();
 
     // Expand the space! Each point after an expansion is offset by the number of
     // empty rows/cols before it times the padding.
     let mut vertices = Vec::new();
     let mut y_offset = 0;
-    for (y, row) in space.iter().enumerate() {
+    for (y, row) in space.rows().enumerate() {
         if empty_row_indices.peek().filter(|&&ey| ey == y).is_some() {
             y_offset += padding;
             empty_row_indices.next();
@@ -53,7 +56,58 @@ fn do_solve(padding: usize) -> usize {
     answer
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Space;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Grid::parse(input, |_, b| match b {
+            b'#' => Ok(true),
+            b'.' => Ok(false),
+            _ => Err("expected a galaxy '#' or empty space '.'"),
+        })
+        .map_err(|err| err.locate(input))
+    }
+
+    fn part1(space: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(do_solve(space, 2))
+    }
+
+    fn part2(space: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(do_solve(space, 1_000_000))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    (do_solve(1), do_solve(1_000_000 - 1))
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("374"), None);
+    }
+
+    #[test]
+    fn larger_expansions() {
+        let space = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(do_solve(&space, 10), 1030);
+        assert_eq!(do_solve(&space, 100), 8410);
+    }
 }
@@ -1,50 +1,50 @@
 use std::{fmt::Display, mem::swap};
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use rustc_hash::FxHashMap as HashMap;
 
 // Adapted from https://redd.it/18gomx5 with the help of ChatGPT and GitHub Copilot!
 
-fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(create_permutations)
+/// A row of springs, each `.`, `#` or `?`, and the sizes of its groups of damaged springs.
+type Record<'a> = (&'a [u8], Vec<usize>);
+
+fn part1(records: &[Record<'_>]) -> usize {
+    #[cfg(feature = "parallel")]
+    let records = records.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let records = records.iter();
+    records
+        .map(|(springs, groups)| create_permutations(springs, groups))
         .sum()
 }
 
-fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .collect::<Vec<_>>()
-        .into_par_iter()
-        .map(unfold)
-        .map(|line| create_permutations(&line))
+fn part2(records: &[Record<'_>]) -> usize {
+    #[cfg(feature = "parallel")]
+    let records = records.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let records = records.iter();
+    records
+        .map(|(springs, groups)| {
+            let (springs, groups) = unfold(springs, groups);
+            create_permutations(&springs, &groups)
+        })
         .sum()
 }
 
-fn unfold(line: &str) -> String {
-    let (springs, config) = line.split_once(' ').unwrap();
-    let mut unfolded_springs = String::with_capacity(6 * springs.len());
-    let mut unfolded_config = String::with_capacity(6 * config.len());
+fn unfold(springs: &[u8], groups: &[usize]) -> (Vec<u8>, Vec<usize>) {
+    let mut unfolded_springs = Vec::with_capacity(5 * springs.len() + 4);
     for i in 0..5 {
         if i != 0 {
-            unfolded_springs.push('?');
-            unfolded_config.push(',');
+            unfolded_springs.push(b'?');
         }
-        unfolded_springs.push_str(springs);
-        unfolded_config.push_str(config);
+        unfolded_springs.extend_from_slice(springs);
     }
-    format!("{} {}", unfolded_springs, unfolded_config)
+    (unfolded_springs, groups.repeat(5))
 }
 
-fn create_permutations(line: &str) -> usize {
-    // Parse the input.
-    let (springs, groups) = line.split_once(' ').unwrap();
-    let groups: Vec<usize> = groups.split(',').map(|s| s.parse().unwrap()).collect();
-    let springs: Vec<u8> = springs.bytes().collect();
-
+fn create_permutations(springs: &[u8], groups: &[usize]) -> usize {
     // Calculate the suffix sums of the groups, as we'll need them later to prune invalid states.
     let mut groups_suffix_sums: Vec<usize> = groups
         .iter()
@@ -100,7 +100,126 @@ fn create_permutations(line: &str) -> usize {
     states.values().copied().sum()
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Vec<Record<'a>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .lines()
+            .map(|line| {
+                let (springs, groups) = line.try_split_once(" ")?;
+                if let Some(idx) = springs.find(|c| !matches!(c, '.' | '#' | '?')) {
+                    return Err(Unexpected::new(&springs[idx..], "expected a spring: '.', '#' or '?'"));
+                }
+                // Parsed as `u32` so that summing the groups can't overflow.
+                let groups = groups
+                    .split(',')
+                    .map(|group| group.try_parse::<u32>().map(|group| group as usize))
+                    .collect::<Result<_, _>>()?;
+                Ok((springs.as_bytes(), groups))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(records: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(part1(records))
+    }
+
+    fn part2(records: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(part2(records))
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let records = Self::parse(input)?;
+        Self::check_assumptions(&records)?;
+        Ok(aoc_common::join(|| part1(&records), || part2(&records)))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    rayon::join(|| part1(include_str!("input.txt")), || part2(include_str!("input.txt")))
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("21"), Some("525152"));
+    }
+
+    /// Try every way of filling in the unknown springs, counting the ones whose damaged groups match.
+    fn brute_force_permutations(springs: &[u8], groups: &[usize]) -> usize {
+        let unknowns = springs.iter().filter(|&&b| b == b'?').count();
+
+        (0..1u32 << unknowns)
+            .filter(|&mask| {
+                let mut unknown_idx = 0;
+                let filled = springs
+                    .iter()
+                    .map(|&b| {
+                        if b != b'?' {
+                            return b;
+                        }
+                        unknown_idx += 1;
+                        if mask & (1 << (unknown_idx - 1)) != 0 {
+                            b'#'
+                        } else {
+                            b'.'
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let damaged = filled
+                    .split(|&b| b == b'.')
+                    .filter(|run| !run.is_empty())
+                    .map(<[u8]>::len)
+                    .collect::<Vec<_>>();
+                damaged == groups
+            })
+            .count()
+    }
+
+    fn record() -> impl Strategy<Value = String> {
+        let springs = proptest::collection::vec(prop_oneof![Just('.'), Just('#'), Just('?')], 1..16)
+            .prop_map(|springs| springs.into_iter().collect::<String>());
+        let groups = proptest::collection::vec(1..5usize, 1..5).prop_map(|groups| {
+            groups
+                .into_iter()
+                .map(|group| group.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+        (springs, groups).prop_map(|(springs, groups)| format!("{springs} {groups}"))
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force(line in record()) {
+            let (springs, groups) = &Puzzle::parse(&line).unwrap()[0];
+            prop_assert_eq!(create_permutations(springs, groups), brute_force_permutations(springs, groups));
+        }
+
+        #[test]
+        fn matches_brute_force_unfolded(springs in "[.#?]{1,3}", group in 1..3usize) {
+            let (springs, groups) = unfold(springs.as_bytes(), &[group]);
+            prop_assert_eq!(create_permutations(&springs, &groups), brute_force_permutations(&springs, &groups));
+        }
+    }
 }
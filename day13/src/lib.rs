@@ -1,67 +1,110 @@
 use std::fmt::Display;
 
-fn count_diffs(a: &str, b: &str) -> usize {
-    a.chars().zip(b.chars()).filter(|(a, b)| a != b).count()
-}
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution};
+use aoc_grid::Grid;
 
-fn process_map(map: &str, smudges: usize) -> usize {
-    // check horizontal line of reflection
-    let rows: Vec<&str> = map.lines().collect();
-    for y in 1..rows.len() {
-        let mut before = &rows[..y];
-        let mut after = &rows[y..];
-        if before.len() < after.len() {
-            after = &after[..before.len()];
-        } else {
-            before = &before[before.len() - after.len()..];
-        }
-        if before
-            .iter()
-            .rev()
-            .zip(after.iter())
-            .map(|(a, b)| count_diffs(a, b))
+/// Every place a pattern `len` rows or columns long folds onto itself with exactly `smudges` differences, given how
+/// many cells differ between any two of those rows or columns.
+fn reflections_along(len: usize, smudges: usize, diffs: impl Fn(usize, usize) -> usize) -> impl Iterator<Item = usize> {
+    (1..len).filter(move |&fold| {
+        (0..fold.min(len - fold))
+            .map(|i| diffs(fold - 1 - i, fold + i))
             .sum::<usize>()
             == smudges
-        {
-            return 100 * y;
-        }
-    }
+    })
+}
+
+/// Every line of reflection with exactly `smudges` differences, summarized as the puzzle asks.
+fn reflections(map: &Grid<bool>, smudges: usize) -> impl Iterator<Item = usize> + '_ {
+    // check horizontal lines of reflection
+    let row_diffs = |a, b| map.row(a).iter().zip(map.row(b)).filter(|(a, b)| a != b).count();
+    let horizontal = reflections_along(map.height(), smudges, row_diffs).map(|y| 100 * y);
+
+    // check vertical lines of reflection
+    let column_diffs = |a, b| map.column(a).zip(map.column(b)).filter(|(a, b)| a != b).count();
+    let vertical = reflections_along(map.width(), smudges, column_diffs);
+
+    horizontal.chain(vertical)
+}
+
+/// Sum up the one line of reflection with exactly `smudges` differences each pattern should have.
+fn summarize(maps: &[Grid<bool>], smudges: usize) -> Result<usize, BrokenAssumption> {
+    maps.iter()
+        .enumerate()
+        .map(|(idx, map)| {
+            let mut found = reflections(map, smudges);
+            match (found.next(), found.count()) {
+                (Some(reflection), 0) => Ok(reflection),
+                (first, rest) => Err(BrokenAssumption::new(format!(
+                    "pattern {} should have exactly one reflection with {smudges} smudges, not {}",
+                    idx + 1,
+                    usize::from(first.is_some()) + rest
+                ))),
+            }
+        })
+        .sum()
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Vec<Grid<bool>>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    // check vertical line of reflection
-    let width = rows[0].len();
-    for x in 1..width {
-        if (0..rows.len())
-            .map(|y| {
-                let mut before = &rows[y][..x];
-                let mut after = &rows[y][x..];
-                if before.len() < after.len() {
-                    after = &after[..before.len()];
-                } else {
-                    before = &before[before.len() - after.len()..];
-                }
-                before.chars().rev().zip(after.chars()).filter(|(a, b)| a != b).count()
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .split("\n\n")
+            .map(|map| {
+                Grid::parse(map, |_, b| match b {
+                    b'#' => Ok(true),
+                    b'.' => Ok(false),
+                    _ => Err("expected ash '.' or rocks '#'"),
+                })
             })
-            .sum::<usize>()
-            == smudges
-        {
-            return x;
-        }
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(maps: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        summarize(maps, 0)
+    }
+
+    fn part2(maps: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        summarize(maps, 1)
     }
+}
 
-    unreachable!()
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
 }
 
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt").split("\n\n");
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
 
-    let part1 = input.clone().map(|map| process_map(map, 0)).sum::<usize>();
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
 
-    let part2 = input.map(|map| process_map(map, 1)).sum::<usize>();
-    debug_assert!(
-        part2 > 10_000 && part2 < 35_975,
-        "{part2} not in correct range! (part1 was {part1})"
-    );
+    use super::*;
 
-    (part1, part2)
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("405"), Some("400"));
+    }
+
+    #[test]
+    fn patterns_without_one_reflection_break_assumptions() {
+        let input = Puzzle::parse("#.\n.#").unwrap();
+        assert_eq!(
+            Puzzle::part1(&input).unwrap_err().reason,
+            "pattern 1 should have exactly one reflection with 0 smudges, not 0"
+        );
+        assert!(solve_input("#.\n.#\n").is_err());
+    }
 }
@@ -1,27 +1,29 @@
 use std::fmt::Display;
 
-// use fxhash::{HashMap, HashMapExt};
-use rustc_hash::FxHashMap as HashMap;
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+use aoc_grid::Grid;
+use aoc_math::brent;
 
-const TOTAL_CYCLES: usize = 1_000_000_000;
-const WIDTH: usize = 100;
-const HEIGHT: usize = 100;
+const TOTAL_CYCLES: u64 = 1_000_000_000;
+/// Each row is a bitset in a `u128`, so that's as wide (and, for simplicity's sake, as tall) as the platform can be.
+const MAX_SIDE: usize = u128::BITS as usize;
 
 const FIRST_COL: u128 = 1;
-const LAST_COL: u128 = 1 << (WIDTH - 1);
 
 // Implementation using u128 inspired by:
 // https://reddit.com/r/adventofcode/comments/18i68p9/2023_day_14_avenues_for_further_optimization/kdet006/
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Map {
-    blocks: [u128; HEIGHT],
-    rollers: [u128; HEIGHT],
+pub struct Map {
+    blocks: [u128; MAX_SIDE],
+    rollers: [u128; MAX_SIDE],
+    width: usize,
+    height: usize,
 }
 
 impl Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
+        for y in 0..self.height {
+            for x in 0..self.width {
                 if self.rollers[y] & (1 << x) != 0 {
                     write!(f, "O")?;
                 } else if self.blocks[y] & (1 << x) != 0 {
@@ -38,9 +40,9 @@ impl Display for Map {
 
 impl Map {
     fn total_load(&self) -> u32 {
-        self.rollers
+        self.rollers[..self.height]
             .iter()
-            .zip((1..=HEIGHT as u32).rev())
+            .zip((1..=self.height as u32).rev())
             .map(|(row, weight)| row.count_ones() * weight)
             .sum()
     }
@@ -48,7 +50,7 @@ impl Map {
     fn tilt_north(&mut self) {
         loop {
             let mut moved = false;
-            for y in (1..HEIGHT).rev() {
+            for y in (1..self.height).rev() {
                 let blockers = self.rollers[y - 1] | self.blocks[y - 1];
                 let north_rollers = self.rollers[y] & !blockers;
                 if north_rollers != 0 {
@@ -66,7 +68,7 @@ impl Map {
     fn tilt_south(&mut self) {
         loop {
             let mut moved = false;
-            for y in 0..(HEIGHT - 1) {
+            for y in 0..(self.height - 1) {
                 let blockers = self.rollers[y + 1] | self.blocks[y + 1];
                 let south_rollers = self.rollers[y] & !blockers;
                 if south_rollers != 0 {
@@ -99,10 +101,11 @@ impl Map {
     }
 
     fn tilt_east(&mut self) {
+        let last_col = 1 << (self.width - 1);
         for (rollers, blocks) in self.rollers.iter_mut().zip(self.blocks) {
             loop {
                 let blockers = *rollers | blocks;
-                let west_rollers = *rollers & !(blockers >> 1) & !LAST_COL;
+                let west_rollers = *rollers & !(blockers >> 1) & !last_col;
                 if west_rollers != 0 {
                     *rollers &= !west_rollers;
                     *rollers |= west_rollers << 1;
@@ -121,48 +124,155 @@ impl Map {
     }
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let mut map = Map {
-        blocks: [0; WIDTH],
-        rollers: [0; WIDTH],
-    };
-
-    include_str!("input.txt")
-        .lines()
-        .zip(map.blocks.iter_mut().zip(map.rollers.iter_mut()))
-        .for_each(|(row, (blocks, rollers))| {
-            row.bytes().enumerate().for_each(|(i, b)| match b {
-                b'O' => *rollers |= 1 << i,
-                b'#' => *blocks |= 1 << i,
-                b'.' => (),
-                _ => unreachable!(),
-            })
-        });
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Map;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let platform = Grid::parse(input, |_, b| match b {
+            b'O' | b'#' | b'.' => Ok(b),
+            _ => Err("expected 'O', '#' or '.'"),
+        })
+        .map_err(|err| err.locate(input))?;
+        let (width, height) = (platform.width(), platform.height());
+        if width > MAX_SIDE || height > MAX_SIDE {
+            let reason = format!("expected a platform at most {MAX_SIDE} cells on each side");
+            return Err(Unexpected::new(input, reason).locate(input));
+        }
+
+        let mut map = Map {
+            blocks: [0; MAX_SIDE],
+            rollers: [0; MAX_SIDE],
+            width,
+            height,
+        };
+
+        for (row, (blocks, rollers)) in platform.rows().zip(map.blocks.iter_mut().zip(map.rollers.iter_mut())) {
+            for (i, &b) in row.iter().enumerate() {
+                match b {
+                    b'O' => *rollers |= 1 << i,
+                    b'#' => *blocks |= 1 << i,
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(map)
+    }
 
-    let part1 = {
-        let mut p1_map = map;
+    fn part1(map: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        let mut p1_map = *map;
         p1_map.tilt_north();
-        p1_map.total_load()
-    };
+        Ok(p1_map.total_load())
+    }
 
-    let mut cycle_nums = HashMap::default();
-    let mut last = 0;
-    let (cycles_before_loop, cycle_len) = (1..)
-        .find_map(|cycle_num| {
+    fn part2(map: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        // The rocks soon settle into going round in circles, so skip ahead by as many of those as possible.
+        let cycle = brent(*map, |map| {
+            let mut map = *map;
             map.spin_cycle();
-            let load = map.total_load();
-            let key = (load, last);
-            last = load;
-            Some((cycle_num, cycle_num - cycle_nums.insert(key, cycle_num)?))
-        })
-        .unwrap();
-    let cycles_remaining = (TOTAL_CYCLES - cycles_before_loop) % cycle_len;
-    for _ in 0..cycles_remaining {
-        map.spin_cycle();
+            map
+        });
+        let mut map = *map;
+        for _ in 0..cycle.earliest(TOTAL_CYCLES) {
+            map.spin_cycle();
+        }
+
+        Ok(map.total_load())
     }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
 
-    let part2 = map.total_load();
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
 
-    (part1, part2)
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("136"), Some("64"));
+    }
+
+    /// Roll every rock one cell at a time, towards `(dy, dx)`, until none of them can move anymore.
+    fn naive_tilt(platform: &mut [Vec<u8>], (dy, dx): (isize, isize)) {
+        let (height, width) = (platform.len(), platform[0].len());
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for y in 0..height {
+                for x in 0..width {
+                    let (Some(ny), Some(nx)) = (y.checked_add_signed(dy), x.checked_add_signed(dx)) else {
+                        continue;
+                    };
+                    if ny < height && nx < width && platform[y][x] == b'O' && platform[ny][nx] == b'.' {
+                        platform[y][x] = b'.';
+                        platform[ny][nx] = b'O';
+                        moved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn naive_load(platform: &[Vec<u8>]) -> usize {
+        platform
+            .iter()
+            .enumerate()
+            .map(|(y, row)| (platform.len() - y) * row.iter().filter(|&&b| b == b'O').count())
+            .sum()
+    }
+
+    fn naive_solve(input: &str) -> (usize, usize) {
+        let mut platform = input.lines().map(|row| row.as_bytes().to_vec()).collect::<Vec<_>>();
+
+        let mut tilted = platform.clone();
+        naive_tilt(&mut tilted, (-1, 0));
+        let part1 = naive_load(&tilted);
+
+        let mut seen = vec![platform.clone()];
+        let (start, len) = loop {
+            for dir in [(-1, 0), (0, -1), (1, 0), (0, 1)] {
+                naive_tilt(&mut platform, dir);
+            }
+            if let Some(start) = seen.iter().position(|earlier| *earlier == platform) {
+                break (start, seen.len() - start);
+            }
+            seen.push(platform.clone());
+        };
+        let part2 = naive_load(&seen[start + (TOTAL_CYCLES as usize - start) % len]);
+
+        (part1, part2)
+    }
+
+    fn platform() -> impl Strategy<Value = String> {
+        (1..12usize, 1..12usize).prop_flat_map(|(width, height)| {
+            let row = proptest::collection::vec(prop_oneof![Just('O'), Just('#'), Just('.')], width)
+                .prop_map(|row| row.into_iter().collect::<String>());
+            proptest::collection::vec(row, height).prop_map(|rows| rows.join("\n"))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive_solution(input in platform()) {
+            let (part1, part2) = Puzzle::solve(&input).unwrap();
+            prop_assert_eq!((part1 as usize, part2 as usize), naive_solve(&input));
+        }
+    }
 }
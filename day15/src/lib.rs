@@ -1,5 +1,6 @@
 use std::{fmt::Display, hash::BuildHasherDefault};
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
 use indexmap::IndexMap;
 use rustc_hash::FxHasher;
 
@@ -7,46 +8,97 @@ fn reindeer_hash(it: impl IntoIterator<Item = u8>) -> u8 {
     it.into_iter().fold(0, |acc, b| acc.wrapping_add(b).wrapping_mul(17))
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let init_seq = include_str!("input.txt").trim();
-
-    let part1 = init_seq
-        .split(',')
-        .map(|step| reindeer_hash(step.bytes()) as u64)
-        .sum::<u64>();
-
-    let mut boxes = vec![IndexMap::with_hasher(BuildHasherDefault::<FxHasher>::default()); 256];
-    for step in init_seq.split(',') {
-        let splat = step.find(&['=', '-'][..]).unwrap();
-        let (label, operation) = step.split_at(splat);
-        let box_idx = reindeer_hash(label.bytes()) as usize;
-        match operation.as_bytes()[0] {
-            b'=' => {
-                let focal_length = operation[1..].parse::<u8>().unwrap();
-                boxes[box_idx].insert(label, focal_length);
-            }
+/// What a step does to the lens with the given label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<'a> {
+    Remove(&'a str),
+    Insert(&'a str, u8),
+}
 
-            b'-' => {
-                boxes[box_idx].shift_remove(label);
-            }
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    /// Each step as written, which is what part 1 hashes, along with what it does.
+    type Input<'a> = Vec<(&'a str, Step<'a>)>;
+    type Part1 = u64;
+    type Part2 = u64;
 
-            _ => unreachable!(),
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        input
+            .trim()
+            .split(',')
+            .map(|step| {
+                let splat = step
+                    .find(&['=', '-'][..])
+                    .ok_or_else(|| Unexpected::new(step, "expected an operation: '=' or '-'"))?;
+                let (label, operation) = step.split_at(splat);
+                let parsed = match operation {
+                    "-" => Step::Remove(label),
+                    operation if operation.starts_with('=') => Step::Insert(label, operation[1..].try_parse()?),
+                    operation => return Err(Unexpected::new(&operation[1..], "expected the end of the step")),
+                };
+                Ok((step, parsed))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.locate(input))
+    }
+
+    fn part1(steps: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(steps
+            .iter()
+            .map(|(step, _)| reindeer_hash(step.bytes()) as u64)
+            .sum::<u64>())
+    }
+
+    fn part2(steps: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        let mut boxes = vec![IndexMap::with_hasher(BuildHasherDefault::<FxHasher>::default()); 256];
+        for &(_, step) in steps {
+            match step {
+                Step::Insert(label, focal_length) => {
+                    boxes[reindeer_hash(label.bytes()) as usize].insert(label, focal_length);
+                }
+
+                Step::Remove(label) => {
+                    boxes[reindeer_hash(label.bytes()) as usize].shift_remove(label);
+                }
+            }
         }
+
+        Ok(boxes
+            .into_iter()
+            .zip(1..)
+            .map(|(contents, weight)| {
+                weight
+                    * contents
+                        .into_iter()
+                        .zip(1..)
+                        .map(|((_, focal_length), slot)| u64::from(focal_length) * slot)
+                        .sum::<u64>()
+            })
+            .sum::<u64>())
     }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
 
-    let part2 = boxes
-        .into_iter()
-        .zip(1..)
-        .map(|(contents, weight)| {
-            weight
-                * contents
-                    .into_iter()
-                    .zip(1..)
-                    .map(|((_, focal_length), slot)| u64::from(focal_length * slot))
-                    .sum::<u64>()
-        })
-        .sum::<u64>();
-
-    (part1, part2)
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("1320"), Some("145"));
+    }
 }
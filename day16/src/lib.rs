@@ -1,14 +1,14 @@
 use std::{cmp::max, fmt::Display, mem::swap};
 
-use grid::Grid;
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution};
+use aoc_grid::{Direction, Grid, Pos};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-type Coordinate = u8;
-type Position = (Coordinate, Coordinate);
 type Map = Grid<Cell>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Cell {
+pub enum Cell {
     Empty,
     ForwardMirror,
     BackMirror,
@@ -16,9 +16,10 @@ enum Cell {
     HorizontalSplitter,
 }
 use Cell::*;
+use Direction::*;
 
 impl Cell {
-    fn apply_to(self, pos: Position, coming_from: Direction, beams: &mut Vec<(Position, Direction)>) {
+    fn apply_to(self, pos: Pos, coming_from: Direction, beams: &mut Vec<(Pos, Direction)>) {
         match (self, coming_from) {
             // Empty tiles or pointy ends just pass the beam through.
             (Empty, _)
@@ -60,50 +61,26 @@ impl Cell {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-use Direction::*;
-
-impl Direction {
-    /// Move the beam in this direction, returning the new position if we didn't underflow.
-    fn apply_to(self, pos: Position) -> Option<Position> {
-        match self {
-            Up => Some((pos.0, pos.1.checked_sub(1)?)),
-            Down => Some((pos.0, pos.1 + 1)),
-            Left => Some((pos.0.checked_sub(1)?, pos.1)),
-            Right => Some((pos.0 + 1, pos.1)),
-        }
-    }
-}
-
-fn do_solve(map: &Map, pushion_point: Position, initial_dir: Direction) -> usize {
+fn do_solve(map: &Map, pushion_point: Pos, initial_dir: Direction) -> usize {
     let mut beams = Vec::default();
     let mut new_beams = Vec::default();
-    let mut seen_beams = Grid::<u8>::new(map.rows(), map.cols());
+    let mut seen_beams = Grid::new(map.width(), map.height(), 0u8);
 
     // Account for the possibility that the pushion point is already some sort of special tile.
-    map[(pushion_point.1 as usize, pushion_point.0 as usize)].apply_to(pushion_point, initial_dir, &mut beams);
+    map[pushion_point].apply_to(pushion_point, initial_dir, &mut beams);
 
     while !beams.is_empty() {
-        for ((x, y), dir) in beams.drain(..) {
+        for (pos, dir) in beams.drain(..) {
             // If there's already been a beam in this spot and moving in this direction, there's no need to recalculate.
-            let pos_seen = &mut seen_beams[(y as usize, x as usize)];
+            let pos_seen = &mut seen_beams[pos];
             if (*pos_seen) & (1 << dir as u8) != 0 {
                 continue;
             }
             *pos_seen |= 1 << dir as u8;
 
             // Apply the tile's effect and ensure we're still on the map.
-            let Some(next_pos) = dir.apply_to((x, y)) else { continue };
-            let Some(next_tile) = map.get(next_pos.1 as usize, next_pos.0 as usize) else {
-                continue;
-            };
-            next_tile.apply_to(next_pos, dir, &mut new_beams);
+            let Some(next_pos) = map.step(pos, dir) else { continue };
+            map[next_pos].apply_to(next_pos, dir, &mut new_beams);
         }
         swap(&mut beams, &mut new_beams);
     }
@@ -111,43 +88,71 @@ fn do_solve(map: &Map, pushion_point: Position, initial_dir: Direction) -> usize
     seen_beams.iter().filter(|&&seen| seen != 0).count()
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let columns = input.lines().next().unwrap().len();
-    let map = input
-        .lines()
-        .flat_map(|line| {
-            line.chars().map(|c| match c {
-                '.' => Empty,
-                '/' => ForwardMirror,
-                '\\' => BackMirror,
-                '|' => VerticalSplitter,
-                '-' => HorizontalSplitter,
-                _ => unreachable!(),
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Map;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Grid::parse(input, |_, b| {
+            Ok(match b {
+                b'.' => Empty,
+                b'/' => ForwardMirror,
+                b'\\' => BackMirror,
+                b'|' => VerticalSplitter,
+                b'-' => HorizontalSplitter,
+                _ => return Err("expected empty space, a mirror or a splitter"),
             })
         })
-        .collect::<Vec<_>>();
-    let map = Map::from_vec(map, columns);
-
-    // Part 1 is simply one iteration of part 2.
-    let part1 = do_solve(&map, (0, 0), Right);
-
-    // Part 2 works by just... brute-forcing every possible starting position! :)
-    let width = map.cols() as Coordinate;
-    let height = map.rows() as Coordinate;
-    let part2 = max(
-        (0..width)
-            .into_par_iter()
-            .map(|x| do_solve(&map, (x, 0), Down).max(do_solve(&map, (x, height - 1), Up)))
-            .max()
-            .unwrap(),
-        (0..height)
-            .into_par_iter()
-            .map(|y| do_solve(&map, (0, y), Right).max(do_solve(&map, (width - 1, y), Left)))
-            .max()
-            .unwrap(),
-    );
-
-    (part1, part2)
+        .map_err(|err| err.locate(input))
+    }
+
+    fn part1(map: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        // Part 1 is simply one iteration of part 2.
+        Ok(do_solve(map, (0, 0), Right))
+    }
+
+    fn part2(map: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        // Part 2 works by just... brute-forcing every possible starting position! :)
+        let (width, height) = (map.width(), map.height());
+        #[cfg(feature = "parallel")]
+        let (columns, rows) = ((0..width).into_par_iter(), (0..height).into_par_iter());
+        #[cfg(not(feature = "parallel"))]
+        let (columns, rows) = (0..width, 0..height);
+        Ok(max(
+            columns
+                .map(|x| do_solve(map, (0, x), Down).max(do_solve(map, (height - 1, x), Up)))
+                .max()
+                .unwrap(),
+            rows.map(|y| do_solve(map, (y, 0), Right).max(do_solve(map, (y, width - 1), Left)))
+                .max()
+                .unwrap(),
+        ))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("46"), Some("51"));
+    }
 }
@@ -3,112 +3,152 @@ use std::fmt::Display;
 // Adaptation of https://www.reddit.com/r/adventofcode/comments/18k9ne5/2023_day_17_solutions/kdqeywd/
 // With the addition of bucket queue + my own optimizations
 
-use ::bucket_queue::*;
-
-const UP: u8 = 0;
-const RIGHT: u8 = 1;
-const DOWN: u8 = 2;
-const LEFT: u8 = 3;
-
-struct Grid {
-    costs: Box<[u8]>,
-    columns: usize,
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution};
+use aoc_grid::{Direction, Grid};
+use aoc_pathfinding::{dijkstra, Graph};
+
+/// The city as the crucible gets around it. Here's the idea: There's no meaningful difference between horizontal and
+/// vertical movement. At each step, we can just go straight any allowed number of straight steps, then turn. So a
+/// state is just a block and whether we got there going vertically (0) or horizontally (1).
+struct Crucible<'a> {
+    city: &'a Grid<u8>,
+    min_straight_steps: usize,
+    max_straight_steps: usize,
 }
 
-impl Grid {
-    fn new(s: &str) -> Self {
-        let mut lines = s.lines().peekable();
-        let columns = lines.peek().map_or(0, |l| l.len());
+impl<'a> Crucible<'a> {
+    fn new(city: &'a Grid<u8>, min_straight_steps: usize, max_straight_steps: usize) -> Self {
         Self {
-            costs: lines.flat_map(str::as_bytes).map(|&c| c - b'0').collect::<Box<_>>(),
-            columns,
+            city,
+            min_straight_steps,
+            max_straight_steps,
         }
     }
-
-    fn step(&self, pos: usize, dir: u8) -> Option<usize> {
-        let rows = self.costs.len();
-        Some(match dir {
-            UP if pos > self.columns => pos - self.columns,
-            RIGHT if (pos + 1) % self.columns != 0 => pos + 1,
-            DOWN if pos < rows - self.columns => pos + self.columns,
-            LEFT if pos % self.columns != 0 => pos - 1,
-            _ => return None,
-        })
-    }
 }
 
-fn do_solve(input: &str, min_straight_steps: usize, max_straight_steps: usize) -> Option<usize> {
-    let grid = Grid::new(input);
-    let start = 0;
-    let goal = grid.costs.len() - 1;
+impl Graph for Crucible<'_> {
+    type Node = (usize, u8);
 
-    let mut visited = vec![0u8; grid.costs.len()];
-    let mut cost_cache = vec![usize::MAX; 2 * grid.costs.len()];
-    let mut q: BucketQueue<Vec<_>> = BucketQueue::new();
-
-    q.push((start, UP), 0);
-    q.push((start, RIGHT), 0);
-
-    // Here's the idea: There's no meaningful difference between horizontal and vertical movement. At each step, we
-    // can just go straight any allowed number of straight steps, then turn.
-
-    while let Some(cost) = q.min_priority() {
-        // Our state is: the current position and what direction we were facing when we got here.
-        let (pos, previous_dir) = q.pop(cost).unwrap();
-
-        // If this is the goal, great!
-        if pos == goal {
-            return Some(cost);
-        }
+    fn node_count(&self) -> usize {
+        2 * self.city.cells().len()
+    }
 
-        // If we've already visited this position from this direction, we can skip it.
-        if visited[pos] & (1u8 << previous_dir) != 0 {
-            continue;
-        }
-        visited[pos] |= 1u8 << previous_dir;
+    fn index(&self, (pos, axis): Self::Node) -> usize {
+        (pos << 1) | axis as usize
+    }
 
-        let left_dir = previous_dir ^ 1;
+    fn successors(&self, (pos, axis): Self::Node, mut edge: impl FnMut(Self::Node, usize)) {
+        // Directions are kept as their index in `Direction::ALL`, where they go clockwise: flipping the lowest bit
+        // turns sideways, and then flipping the next one turns around.
+        let left_dir = axis ^ 1;
         let right_dir = left_dir ^ 2;
         // For each possible turning direction...
         'turnloop: for turn_dir in [left_dir, right_dir] {
-            let mut new_cost = cost;
+            let step = Direction::ALL[turn_dir as usize];
+            let mut new_cost = 0;
             let mut new_pos = pos;
 
             // Go straight in the new direction the requisite amount of steps.
-            for _ in 1..min_straight_steps {
-                new_pos = if let Some(np) = grid.step(new_pos, turn_dir) {
+            for _ in 1..self.min_straight_steps {
+                new_pos = if let Some(np) = self.city.step_index(new_pos, step) {
                     np
                 } else {
                     continue 'turnloop;
                 };
-                new_cost += grid.costs[new_pos] as usize;
+                new_cost += self.city.cells()[new_pos] as usize;
             }
 
             // After we've exhausted the minimum number of straight steps, we can choose to stop walking and allow
             // another opportunity to turn.
-            for _ in min_straight_steps..=max_straight_steps {
-                new_pos = if let Some(np) = grid.step(new_pos, turn_dir) {
+            for _ in self.min_straight_steps..=self.max_straight_steps {
+                new_pos = if let Some(np) = self.city.step_index(new_pos, step) {
                     np
                 } else {
                     break;
                 };
-                new_cost += grid.costs[new_pos] as usize;
-
-                // Stop walking! We only add the state to the queue if it's better than what we've seen before.
-                let cache_idx = (new_pos << 1) | left_dir as usize;
-                if cost_cache[cache_idx] > new_cost {
-                    cost_cache[cache_idx] = new_cost;
-                    q.push((new_pos, left_dir), new_cost);
-                }
+                new_cost += self.city.cells()[new_pos] as usize;
+                edge((new_pos, left_dir), new_cost);
             }
         }
     }
+}
+
+const STARTS: [(usize, u8); 2] = [(0, 0), (0, 1)];
+
+fn do_solve(city: &Grid<u8>, min_straight_steps: usize, max_straight_steps: usize) -> Option<usize> {
+    let crucible = Crucible::new(city, min_straight_steps, max_straight_steps);
+    let goal = city.cells().len() - 1;
+    let search = dijkstra(&crucible, STARTS, |(pos, _)| pos == goal);
+    search.goal().map(|(_, cost)| cost)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Grid<u8>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Grid::parse(input, |_, b| match b {
+            b'1'..=b'9' => Ok(b - b'0'),
+            _ => Err("expected a heat loss digit from 1 to 9"),
+        })
+        .map_err(|err| err.locate(input))
+    }
+
+    fn part1(grid: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        do_solve(grid, 1, 3).ok_or_else(|| BrokenAssumption::new("the crucible should be able to reach the factory"))
+    }
+
+    fn part2(grid: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        do_solve(grid, 4, 10)
+            .ok_or_else(|| BrokenAssumption::new("the ultra crucible should be able to reach the factory"))
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let grid = Self::parse(input)?;
+        let (part1, part2) = aoc_common::join(|| Self::part1(&grid), || Self::part2(&grid));
+        Ok((part1?, part2?))
+    }
+}
 
-    None
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
 }
 
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    rayon::join(|| do_solve(input, 0, 3).unwrap(), || do_solve(input, 4, 10).unwrap())
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("102"), Some("94"));
+    }
+
+    #[test]
+    fn part2_unfortunate_example() {
+        check_example::<Puzzle>(include_str!("sample_input_2.txt"), None, Some("71"));
+    }
+
+    #[test]
+    fn unreachable_factories_break_assumptions() {
+        // The ultra crucible can't stop after only two blocks.
+        let grid = Puzzle::parse("123").unwrap();
+        assert_eq!(
+            Puzzle::part2(&grid).unwrap_err().reason,
+            "the ultra crucible should be able to reach the factory"
+        );
+        assert!(solve_input("123\n").is_err());
+    }
 }
@@ -1,7 +1,10 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_geometry::{Point, Polygon};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -9,91 +12,260 @@ enum Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Instruction {
+pub struct Instruction {
     direction: Direction,
     steps: i64,
 }
 
-// Solve using Pick's theorem.
-fn do_solve(instructions: &[Instruction]) -> i64 {
-    // Calculate the vertices by following the instructions. Along the way, it's easy to calculate the perimeter.
-    let mut x = 0;
-    let mut y = 0;
-    let mut vertices = vec![(0, 0)];
-    vertices.reserve(instructions.len() + 1);
-    let mut perimeter = 0;
-    for &Instruction { direction, steps } in instructions {
-        let destination = match direction {
-            Direction::Up => (x, y + steps),
-            Direction::Down => (x, y - steps),
-            Direction::Left => (x - steps, y),
-            Direction::Right => (x + steps, y),
-        };
-        (x, y) = destination;
-        vertices.push(destination);
-        perimeter += steps;
+/// How far each instruction moves, as `(dx, dy)`.
+fn moves(instructions: &[Instruction]) -> impl Iterator<Item = Point> + '_ {
+    instructions.iter().map(|&Instruction { direction, steps }| {
+        let steps = i128::from(steps);
+        match direction {
+            Direction::Up => (0, steps),
+            Direction::Down => (0, -steps),
+            Direction::Left => (-steps, 0),
+            Direction::Right => (steps, 0),
+        }
+    })
+}
+
+// Solve using Pick's theorem, counting the cells the trench runs through as well as the ones it encloses.
+fn do_solve(instructions: &[Instruction]) -> i128 {
+    let trench = Polygon::from_steps(moves(instructions));
+    trench.interior_points() + trench.boundary_points()
+}
+
+/// Check that the trench comes back round to where it started. That it doesn't cross or touch itself on the way is
+/// taken on trust, since checking means comparing every pair of edges.
+fn check_plan(instructions: &[Instruction]) -> Result<(), BrokenAssumption> {
+    let end = moves(instructions).fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy));
+    if end != (0, 0) {
+        return Err(BrokenAssumption::new(format!(
+            "the trench should end where it started, not at {end:?}"
+        )));
     }
+    Ok(())
+}
 
-    // Utilize the shoelace formula to calculate the area, taking the absolute value to avoid depending on the order of
-    // the vertices.
-    let area = vertices
-        .windows(2)
-        .map(|w| {
-            let (x1, y1) = w[0];
-            let (x2, y2) = w[1];
-            x1 * y2 - x2 * y1
-        })
-        .sum::<i64>()
-        .abs()
-        / 2;
-
-    // Pick's theorem states that:
-    // Area = InteriorIntegerPoints + BoundaryIntegerPoints / 2 - 1
-    // This means that we can calculate the number of interior integer points by:
-    // InteriorIntegerPoints = Area - BoundaryIntegerPoints / 2 + 1
-    // However, to that we must add the perimeter, as the problem description asks us to count the points on the
-    // boundary as well. Therefore, the final formula is:
-    area + perimeter / 2 + 1
+/// Parse a line of the dig plan into the instruction it holds for each part.
+fn parse_instructions(line: &str) -> Result<(Instruction, Instruction), Unexpected<'_>> {
+    let (p1_direction, rest) = line.try_split_once(" ")?;
+    let (p1_steps, color) = rest.try_split_once(" ")?;
+    let p1_direction = match p1_direction {
+        "U" => Direction::Up,
+        "D" => Direction::Down,
+        "L" => Direction::Left,
+        "R" => Direction::Right,
+        _ => {
+            return Err(Unexpected::new(
+                p1_direction,
+                "expected a direction: 'U', 'D', 'L' or 'R'",
+            ))
+        }
+    };
+    let p1_steps = p1_steps.try_parse::<u32>()?;
+
+    let hex = color
+        .strip_prefix("(#")
+        .and_then(|color| color.strip_suffix(')'))
+        .filter(|hex| hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| Unexpected::new(color, "expected a color like (#70c710)"))?;
+    let (p2_steps, p2_direction) = hex.split_at(hex.len() - 1);
+    let p2_steps = u32::from_str_radix(p2_steps, 16).unwrap();
+    let p2_direction = match p2_direction {
+        "0" => Direction::Right,
+        "1" => Direction::Down,
+        "2" => Direction::Left,
+        "3" => Direction::Up,
+        _ => return Err(Unexpected::new(p2_direction, "expected a direction digit from 0 to 3")),
+    };
+
+    Ok((
+        Instruction {
+            direction: p1_direction,
+            steps: p1_steps.into(),
+        },
+        Instruction {
+            direction: p2_direction,
+            steps: p2_steps.into(),
+        },
+    ))
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    /// The instructions as read in part 1, then as decoded from the colors in part 2.
+    type Input<'a> = (Vec<Instruction>, Vec<Instruction>);
+    type Part1 = i128;
+    type Part2 = i128;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Ok(input
+            .lines()
+            .map(parse_instructions)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.locate(input))?
+            .into_iter()
+            .unzip())
+    }
+
+    fn part1((part1_instructions, _): &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(do_solve(part1_instructions))
+    }
+
+    fn part2((_, part2_instructions): &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(do_solve(part2_instructions))
+    }
+
+    fn check_assumptions((part1_instructions, part2_instructions): &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        check_plan(part1_instructions)?;
+        check_plan(part2_instructions)
+    }
 }
 
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let (part1_instructions, part2_instructions): (Vec<_>, Vec<_>) = include_str!("input.txt")
-        .lines()
-        .map(|line| {
-            let mut it = line.split(' ');
-            let p1_direction = match it.next().unwrap() {
-                "U" => Direction::Up,
-                "D" => Direction::Down,
-                "L" => Direction::Left,
-                "R" => Direction::Right,
-                _ => unreachable!(),
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("62"), Some("952408144115"));
+    }
+
+    #[test]
+    fn open_plan_breaks_assumptions() {
+        let input = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&input), Ok(()));
+
+        let input = Puzzle::parse("R 2 (#000020)\nD 1 (#000011)").unwrap();
+        assert!(Puzzle::check_assumptions(&input).is_err());
+    }
+
+    const MAX_HEIGHT: i64 = 8;
+
+    /// Dig the trench out on a grid, then flood the outside of it and count what's left.
+    fn naive_solve(instructions: &[Instruction]) -> usize {
+        let mut trench = vec![(0, 0)];
+        for &Instruction { direction, steps } in instructions {
+            for _ in 0..steps {
+                let (x, y) = *trench.last().unwrap();
+                trench.push(match direction {
+                    Direction::Up => (x, y + 1),
+                    Direction::Down => (x, y - 1),
+                    Direction::Left => (x - 1, y),
+                    Direction::Right => (x + 1, y),
+                });
+            }
+        }
+
+        // Leave a border around the trench so the outside is all connected.
+        let min_x = trench.iter().map(|&(x, _)| x).min().unwrap() - 1;
+        let min_y = trench.iter().map(|&(_, y)| y).min().unwrap() - 1;
+        let width = (trench.iter().map(|&(x, _)| x).max().unwrap() - min_x + 2) as usize;
+        let height = (trench.iter().map(|&(_, y)| y).max().unwrap() - min_y + 2) as usize;
+        let mut dug = vec![vec![false; width]; height];
+        for (x, y) in trench {
+            dug[(y - min_y) as usize][(x - min_x) as usize] = true;
+        }
+
+        let mut outside = vec![vec![false; width]; height];
+        let mut stack = vec![(0, 0)];
+        while let Some((x, y)) = stack.pop() {
+            if x >= width || y >= height || dug[y][x] || outside[y][x] {
+                continue;
+            }
+            outside[y][x] = true;
+            stack.extend([(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))]);
+        }
+
+        width * height - outside.iter().flatten().filter(|&&o| o).count()
+    }
+
+    /// A dig plan tracing the outline of side-by-side columns of cells, each of them overlapping the next, so that the
+    /// trench never crosses or touches itself.
+    fn dig_plan() -> impl Strategy<Value = Vec<Instruction>> {
+        let column = (1..4i64, 0..MAX_HEIGHT, 0..MAX_HEIGHT);
+        (proptest::collection::vec(column, 1..8), any::<bool>()).prop_map(|(columns, reversed)| {
+            // Turn the random numbers into `(width, bottom, top)` columns that overlap their predecessors.
+            let mut spans: Vec<(i64, i64, i64)> = Vec::with_capacity(columns.len());
+            for (width, a, b) in columns {
+                let (bottom, top) = match spans.last() {
+                    None => (a.min(b), a.max(b) + 1),
+                    Some(&(_, prev_bottom, prev_top)) => {
+                        let bottom = a % prev_top;
+                        (bottom, (bottom.max(prev_bottom) + 1 + b).min(MAX_HEIGHT))
+                    }
+                };
+                spans.push((width, bottom, top));
+            }
+
+            let mut instructions = Vec::new();
+            let mut step = |direction, steps: i64| {
+                if steps != 0 {
+                    instructions.push(Instruction { direction, steps });
+                }
             };
-            let p1_steps = it.next().unwrap().parse().unwrap();
-
-            let color = &it.next().unwrap()[2..8];
-            let (p2_steps, p2_direction) = color.split_at(color.len() - 1);
-            let p2_steps = u32::from_str_radix(p2_steps, 16).unwrap();
-            let p2_direction = match p2_direction {
-                "0" => Direction::Right,
-                "1" => Direction::Down,
-                "2" => Direction::Left,
-                "3" => Direction::Up,
-                _ => unreachable!(),
+            let vertical = |from: i64, to: i64| {
+                if to > from {
+                    (Direction::Up, to - from)
+                } else {
+                    (Direction::Down, from - to)
+                }
             };
 
-            (
-                Instruction {
-                    direction: p1_direction,
-                    steps: p1_steps,
-                },
-                Instruction {
-                    direction: p2_direction,
-                    steps: p2_steps as _,
-                },
-            )
+            // Along the top from left to right, then along the bottom from right to left.
+            let (_, first_bottom, first_top) = spans[0];
+            step(Direction::Up, first_top - first_bottom);
+            for (idx, &(width, _, top)) in spans.iter().enumerate() {
+                step(Direction::Right, width);
+                let (direction, steps) = vertical(top, spans.get(idx + 1).map_or(spans[idx].1, |span| span.2));
+                step(direction, steps);
+            }
+            for (idx, &(width, bottom, _)) in spans.iter().enumerate().rev() {
+                step(Direction::Left, width);
+                if idx > 0 {
+                    let (direction, steps) = vertical(bottom, spans[idx - 1].1);
+                    step(direction, steps);
+                }
+            }
+
+            if reversed {
+                instructions.reverse();
+                for instruction in &mut instructions {
+                    instruction.direction = match instruction.direction {
+                        Direction::Up => Direction::Down,
+                        Direction::Down => Direction::Up,
+                        Direction::Left => Direction::Right,
+                        Direction::Right => Direction::Left,
+                    };
+                }
+            }
+            instructions
         })
-        .unzip();
+    }
 
-    (do_solve(&part1_instructions), do_solve(&part2_instructions))
+    proptest! {
+        #[test]
+        fn matches_naive_solution(instructions in dig_plan()) {
+            prop_assert_eq!(check_plan(&instructions), Ok(()));
+            prop_assert_eq!(do_solve(&instructions) as usize, naive_solve(&instructions));
+        }
+    }
 }
@@ -1,8 +1,11 @@
 use std::fmt::Display;
 
-use rustc_hash::FxHashMap as HashMap;
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_intervals::{Cuboid, Interval};
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
 type WorkflowId = u32;
+type Workflows = HashMap<WorkflowId, Workflow>;
 type Value = u64;
 
 const fn str2id(s: &[u8]) -> WorkflowId {
@@ -14,6 +17,24 @@ const fn str2id(s: &[u8]) -> WorkflowId {
     }
 }
 
+fn parse_id(s: &str) -> Result<WorkflowId, Unexpected<'_>> {
+    if !(1..=3).contains(&s.len()) {
+        return Err(Unexpected::new(
+            s,
+            format!("expected a workflow name of up to 3 letters, got {s:?}"),
+        ));
+    }
+    Ok(str2id(s.as_bytes()))
+}
+
+fn workflow_name(id: WorkflowId) -> String {
+    id.to_be_bytes()
+        .into_iter()
+        .filter(|&b| b != 0)
+        .map(char::from)
+        .collect()
+}
+
 const ACCEPT: WorkflowId = str2id(b"A");
 const REJECT: WorkflowId = str2id(b"R");
 const INITIAL_WORKFLOW: WorkflowId = str2id(b"in");
@@ -27,21 +48,24 @@ enum Property {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Part {
+pub struct Part {
     properties: [Value; 4],
 }
 
 impl Part {
-    fn parse(s: &str) -> Self {
+    fn parse(s: &str) -> Result<Self, Unexpected<'_>> {
         let mut this = Self { properties: [0; 4] };
-        s.strip_prefix('{')
-            .unwrap()
-            .strip_suffix('}')
-            .unwrap()
-            .split(',')
-            .zip(this.properties.iter_mut())
-            .for_each(|(val, prop)| *prop = val.split_once('=').unwrap().1.parse::<Value>().unwrap());
-        this
+        let ratings = s
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(|| Unexpected::new(s, "expected a part like {x=1,m=2,a=3,s=4}"))?;
+        if ratings.split(',').count() != this.properties.len() {
+            return Err(Unexpected::new(ratings, "expected exactly four ratings"));
+        }
+        for (val, prop) in ratings.split(',').zip(this.properties.iter_mut()) {
+            *prop = val.try_split_once("=")?.1.try_parse::<Value>()?;
+        }
+        Ok(this)
     }
 }
 
@@ -68,60 +92,148 @@ impl Rule {
         }
     }
 
-    fn parse(rule: &str) -> Self {
-        let (comparison, send_to) = rule.split_once(':').unwrap();
-        let threshold = comparison[2..].parse::<Value>().unwrap();
-        let property = match comparison.as_bytes()[0] {
-            b'x' => Property::ExtremelyCoolLooking,
-            b'm' => Property::Musical,
-            b'a' => Property::Aerodynamic,
-            b's' => Property::Shiny,
-            _ => panic!("Invalid property"),
+    fn parse(rule: &str) -> Result<Self, Unexpected<'_>> {
+        let (comparison, send_to) = rule.try_split_once(":")?;
+        let Some((property, rest)) = comparison.split_at_checked(1) else {
+            return Err(Unexpected::new(comparison, "expected a property"));
+        };
+        let property = match property {
+            "x" => Property::ExtremelyCoolLooking,
+            "m" => Property::Musical,
+            "a" => Property::Aerodynamic,
+            "s" => Property::Shiny,
+            _ => return Err(Unexpected::new(property, format!("unknown property {property:?}"))),
         };
-        let comparison = match comparison.as_bytes()[1] {
-            b'<' => Comparison::LessThan,
-            b'>' => Comparison::GreaterThan,
-            _ => panic!("Invalid comparison operator"),
+        let Some((operator, threshold)) = rest.split_at_checked(1) else {
+            return Err(Unexpected::new(rest, "expected a comparison operator"));
+        };
+        let comparison = match operator {
+            "<" => Comparison::LessThan,
+            ">" => Comparison::GreaterThan,
+            _ => {
+                return Err(Unexpected::new(
+                    operator,
+                    format!("unknown comparison operator {operator:?}"),
+                ))
+            }
         };
 
-        Rule {
+        Ok(Rule {
             property,
             comparison,
-            threshold,
-            send_to: str2id(send_to.as_bytes()),
-        }
+            threshold: threshold.try_parse::<Value>()?,
+            send_to: parse_id(send_to)?,
+        })
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Workflow {
+pub struct Workflow {
     rules: Box<[Rule]>,
     final_destination: WorkflowId,
 }
 
 impl Workflow {
-    fn parse(s: &'static str) -> (WorkflowId, Self) {
-        let (id, rules) = s.strip_suffix('}').unwrap().split_once('{').unwrap();
+    /// Parse a workflow along with its name.
+    fn parse(s: &str) -> Result<(&str, Self), Unexpected<'_>> {
+        let (id, rules) = s
+            .strip_suffix('}')
+            .ok_or_else(|| Unexpected::new(s, "expected a workflow like px{a<2006:qkq,rfg}"))?
+            .try_split_once("{")?;
         let mut rules = rules.split(',');
-        let final_destination = str2id(rules.next_back().unwrap().as_bytes());
-        let mut rules: Box<[Rule]> = rules.map(Rule::parse).collect();
+        // `split` always yields at least once.
+        let final_destination = parse_id(rules.next_back().unwrap())?;
+        let mut rules: Box<[Rule]> = rules.map(Rule::parse).collect::<Result<_, _>>()?;
 
         if rules.iter().all(|rule| rule.send_to == final_destination) {
             rules = Box::new([]);
         }
 
-        (
-            str2id(id.as_bytes()),
+        Ok((
+            id,
             Self {
                 rules,
                 final_destination,
             },
-        )
+        ))
+    }
+
+    /// Every workflow this one can send parts to.
+    fn destinations(&self) -> impl Iterator<Item = WorkflowId> + '_ {
+        self.rules
+            .iter()
+            .map(|rule| rule.send_to)
+            .chain([self.final_destination])
+    }
+}
+
+/// Parse the workflows, checking that each has its own name, that there's one to start from, and that every rule
+/// sends parts somewhere that exists.
+fn parse_workflows(text: &str) -> Result<Workflows, Unexpected<'_>> {
+    let mut workflows = Workflows::default();
+    let mut names = Vec::new();
+    for line in text.lines() {
+        let (name, workflow) = Workflow::parse(line)?;
+        let id = parse_id(name)?;
+        if workflows.insert(id, workflow).is_some() {
+            return Err(Unexpected::new(name, format!("workflow {name} is defined twice")));
+        }
+        names.push((name, id));
+    }
+
+    if !workflows.contains_key(&INITIAL_WORKFLOW) {
+        return Err(Unexpected::new(text, "expected a workflow named in to start from"));
+    }
+    for (name, id) in names {
+        let is_known = |id| id == ACCEPT || id == REJECT || workflows.contains_key(&id);
+        if let Some(unknown) = workflows[&id].destinations().find(|&id| !is_known(id)) {
+            return Err(Unexpected::new(
+                name,
+                format!(
+                    "workflow {name} sends parts to unknown workflow {}",
+                    workflow_name(unknown)
+                ),
+            ));
+        }
+    }
+    Ok(workflows)
+}
+
+/// Find a workflow that parts can get sent back to after going through it, which would send some of them round in
+/// circles forever.
+fn find_loop(workflows: &Workflows) -> Option<WorkflowId> {
+    fn visit(
+        workflows: &Workflows,
+        id: WorkflowId,
+        on_path: &mut HashSet<WorkflowId>,
+        done: &mut HashSet<WorkflowId>,
+    ) -> Option<WorkflowId> {
+        if id == ACCEPT || id == REJECT || done.contains(&id) {
+            return None;
+        }
+        if !on_path.insert(id) {
+            return Some(id);
+        }
+        for send_to in workflows[&id].destinations() {
+            if let Some(id) = visit(workflows, send_to, on_path, done) {
+                return Some(id);
+            }
+        }
+        on_path.remove(&id);
+        done.insert(id);
+        None
     }
+
+    visit(
+        workflows,
+        INITIAL_WORKFLOW,
+        &mut HashSet::default(),
+        &mut HashSet::default(),
+    )
 }
 
-fn is_accepted(workflows: &HashMap<WorkflowId, Workflow>, part: &Part) -> bool {
-    let mut workflow = workflows.get(&INITIAL_WORKFLOW).unwrap();
+fn is_accepted(workflows: &Workflows, part: &Part) -> bool {
+    let mut workflow = &workflows[&INITIAL_WORKFLOW];
     loop {
         let rule = workflow.rules.iter().find(|rule| rule.matches(part));
         let send_to = match rule {
@@ -131,85 +243,42 @@ fn is_accepted(workflows: &HashMap<WorkflowId, Workflow>, part: &Part) -> bool {
         match send_to {
             ACCEPT => return true,
             REJECT => return false,
-            _ => workflow = workflows.get(&send_to).unwrap(),
+            _ => workflow = &workflows[&send_to],
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct HypotheticalPart {
-    min: [Value; 4],
-    max: [Value; 4],
-}
+/// Every part whose ratings fall in each of the ranges.
+type HypotheticalPart = Cuboid<4>;
 
-fn do_part2(workflows: &HashMap<WorkflowId, Workflow>, workflow_id: WorkflowId, mut part: HypotheticalPart) -> Value {
+fn do_part2(workflows: &Workflows, workflow_id: WorkflowId, mut part: HypotheticalPart) -> Value {
     if workflow_id == ACCEPT {
-        return part
-            .max
-            .iter()
-            .zip(part.min.iter())
-            .map(|(max, min)| max - min + 1)
-            .product();
+        return part.volume();
     } else if workflow_id == REJECT {
         return 0;
     }
 
-    let workflow = workflows.get(&workflow_id).unwrap();
+    let workflow = &workflows[&workflow_id];
     let mut answer = 0;
 
     for rule in workflow.rules.iter() {
         let idx = rule.property as usize;
-        match rule.comparison {
-            Comparison::LessThan => {
-                // min..minimum(max, threshold) is the bit that matches
-                // max..threshold is the bit that doesn't match
-                let matching_part = (part.min[idx] < rule.threshold).then(|| {
-                    let mut new_part = part;
-                    new_part.max[idx] = new_part.max[idx].min(rule.threshold - 1);
-                    new_part
-                });
-
-                let non_matching_part = (part.max[idx] >= rule.threshold).then(|| {
-                    let mut new_part = part;
-                    new_part.min[idx] = rule.threshold;
-                    new_part.max[idx] = part.max[idx];
-                    new_part
-                });
-
-                if let Some(matching_part) = matching_part {
-                    answer += do_part2(workflows, rule.send_to, matching_part);
-                }
-
-                if let Some(non_matching_part) = non_matching_part {
-                    part = non_matching_part;
-                } else {
-                    break;
-                }
-            }
+        let (matching_part, non_matching_part) = match rule.comparison {
+            Comparison::LessThan => part.split_at(idx, rule.threshold),
             Comparison::GreaterThan => {
-                let matching_part = (part.max[idx] > rule.threshold).then(|| {
-                    let mut new_part = part;
-                    new_part.min[idx] = new_part.min[idx].max(rule.threshold + 1);
-                    new_part
-                });
-
-                let non_matching_part = (part.min[idx] <= rule.threshold).then(|| {
-                    let mut new_part = part;
-                    new_part.min[idx] = part.min[idx];
-                    new_part.max[idx] = rule.threshold;
-                    new_part
-                });
-
-                if let Some(matching_part) = matching_part {
-                    answer += do_part2(workflows, rule.send_to, matching_part);
-                }
-
-                if let Some(non_matching_part) = non_matching_part {
-                    part = non_matching_part;
-                } else {
-                    break;
-                }
+                let (non_matching_part, matching_part) = part.split_at(idx, rule.threshold + 1);
+                (matching_part, non_matching_part)
             }
+        };
+
+        if let Some(matching_part) = matching_part {
+            answer += do_part2(workflows, rule.send_to, matching_part);
+        }
+
+        match non_matching_part {
+            Some(non_matching_part) => part = non_matching_part,
+            // Every part matched, so there are none left for the rest of the workflow.
+            None => return answer,
         }
     }
 
@@ -218,26 +287,105 @@ fn do_part2(workflows: &HashMap<WorkflowId, Workflow>, workflow_id: WorkflowId,
     answer
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = (Workflows, Vec<Part>);
+    type Part1 = Value;
+    type Part2 = Value;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let parse = || {
+            let (text, parts) = input.try_split_once("\n\n")?;
+            let workflows = parse_workflows(text)?;
+            Ok((workflows, parts.lines().map(Part::parse).collect::<Result<_, _>>()?))
+        };
+        parse().map_err(|err: Unexpected| err.locate(input))
+    }
+
+    fn part1((workflows, parts): &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(parts
+            .iter()
+            .filter(|part| is_accepted(workflows, part))
+            .map(|part| part.properties.into_iter().sum::<Value>())
+            .sum::<Value>())
+    }
+
+    fn check_assumptions((workflows, _): &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        match find_loop(workflows) {
+            Some(id) => Err(BrokenAssumption::new(format!(
+                "workflows should never send parts back to {}, or they could go round forever",
+                workflow_name(id)
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn part2((workflows, _): &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(do_part2(
+            workflows,
+            INITIAL_WORKFLOW,
+            HypotheticalPart {
+                axes: [Interval::new(1, 4001); 4],
+            },
+        ))
+    }
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let (workflows, parts) = include_str!("input.txt").split_once("\n\n").unwrap();
-    let workflows = workflows.lines().map(Workflow::parse).collect();
-
-    let part1 = parts
-        .lines()
-        .map(Part::parse)
-        .filter(|part| is_accepted(&workflows, part))
-        .map(|part| part.properties.into_iter().sum::<Value>())
-        .sum::<Value>();
-
-    let part2 = do_part2(
-        &workflows,
-        INITIAL_WORKFLOW,
-        HypotheticalPart {
-            min: [1; 4],
-            max: [4000; 4],
-        },
-    );
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
 
-    (part1, part2)
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("19114"), Some("167409079868000"));
+    }
+
+    #[test]
+    fn rejects_unknown_workflows() {
+        let err = Puzzle::parse("px{a<2006:qkq,m>2090:A,rfg}\nin{s<1351:px,R}\n\n{x=787,m=2655,a=1222,s=2876}\n");
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 1, column 1: workflow px sends parts to unknown workflow qkq"
+        );
+
+        let err = Puzzle::parse("px{a<2006:A,R}\n\n{x=787,m=2655,a=1222,s=2876}\n");
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 1, column 1: expected a workflow named in to start from"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_workflows() {
+        let err = Puzzle::parse("in{a<2006:px,A}\npx{m>2090:A,R}\npx{s>3448:A,R}\n\n{x=1,m=2,a=3,s=4}\n");
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 3, column 1: workflow px is defined twice"
+        );
+    }
+
+    #[test]
+    fn loops_break_assumptions() {
+        let input = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&input), Ok(()));
+
+        let input = Puzzle::parse("in{a<2006:px,A}\npx{m>2090:R,qs}\nqs{s>3448:A,in}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        let err = Puzzle::check_assumptions(&input).unwrap_err();
+        assert!(err.reason.contains(" in,"), "{err}");
+    }
 }
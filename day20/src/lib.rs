@@ -1,16 +1,32 @@
 use std::{collections::VecDeque, fmt::Display};
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_math::Cycle;
 use rustc_hash::FxHashMap as HashMap;
 
 type ModuleName = u16;
 
 const fn str2name(s: &[u8]) -> ModuleName {
-    u16::from_be_bytes([s[0], s[1]])
+    if s.len() == 1 {
+        u16::from_be_bytes([s[0], 0])
+    } else {
+        u16::from_be_bytes([s[0], s[1]])
+    }
+}
+
+fn parse_name(s: &str) -> Result<ModuleName, Unexpected<'_>> {
+    if s.is_empty() {
+        return Err(Unexpected::new(s, "expected a module name"));
+    }
+    Ok(str2name(s.as_bytes()))
 }
 
 const BROADCASTER: ModuleName = str2name(b"broadcaster");
 const BUTTON: ModuleName = str2name(b"button");
 
+const COUNTERS: usize = 4;
+const COUNTER_BITS: usize = 12;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModuleType {
     Broadcaster,
@@ -25,21 +41,23 @@ pub struct Module {
 }
 
 impl Module {
-    pub fn parse(s: &'static str) -> (ModuleName, Self) {
-        let (lhs, rhs) = s.split_once(" -> ").unwrap();
-        let destinations: Box<[_]> = rhs.split(", ").map(|s| str2name(s.as_bytes())).collect();
+    pub fn parse(s: &str) -> Result<(ModuleName, Self), Unexpected<'_>> {
+        let (lhs, rhs) = s.try_split_once(" -> ")?;
+        let destinations: Box<[_]> = rhs.split(", ").map(parse_name).collect::<Result<_, _>>()?;
         let (ty, name) = if lhs == "broadcaster" {
             (ModuleType::Broadcaster, BROADCASTER)
         } else {
-            let (ty_marker, name) = lhs.split_at(1);
+            let (ty_marker, name) = lhs
+                .split_at_checked(1)
+                .ok_or_else(|| Unexpected::new(lhs, "expected a module type"))?;
             let ty = match ty_marker {
                 "%" => ModuleType::FlipFlop(false),
                 "&" => ModuleType::Nand(Default::default()),
-                _ => panic!("Invalid module type {ty_marker:?}"),
+                _ => return Err(Unexpected::new(ty_marker, format!("unknown module type {ty_marker:?}"))),
             };
-            (ty, str2name(name.as_bytes()))
+            (ty, parse_name(name)?)
         };
-        (name, Self { ty, destinations })
+        Ok((name, Self { ty, destinations }))
     }
 
     pub fn is_nand(&self) -> bool {
@@ -110,66 +128,177 @@ fn solve_part1(mut modules: HashMap<ModuleName, Module>) -> usize {
 }
 
 // Part 2 involves noticing that the graph can be split into four 12-bit counters, each that reset and send a HIGH pulse
-// to an output NAND gate when they reach a certain value. Each counter therefore has a specific cycle length that is,
-// by construction of the input, prime; we can find the answer by multiplying the cycle lengths of each counter.
+// to an output NAND gate when they reach a certain value. Each counter therefore has a specific cycle length; we can
+// find the answer by working out when all of those cycles line up.
 fn solve_part2(modules: &HashMap<ModuleName, Module>) -> u64 {
-    modules[&BROADCASTER]
-        .destinations
-        .iter()
-        .map(|mut bit| {
-            let mut period = 0u64;
-
-            // Walk the graph until we reach the leaf flipflop, setting a bit on the period for each flipflop that must
-            // be ON for the counter to reset.
-            loop {
-                let children = &*modules[bit].destinations;
-
-                let should_count = children.iter().any(|c| modules[c].is_nand());
-                if should_count {
-                    period |= 1;
+    let counters = modules[&BROADCASTER].destinations.iter().map(|&bit| {
+        let period = counter_period(modules, bit);
+        Cycle { start: period, period }
+    });
+    // Counters of 12 bits can't take long enough to line up to overflow.
+    Cycle::align(counters).unwrap().start
+}
+
+/// The cycle length of the counter whose lowest bit is the flipflop `bit`.
+fn counter_period(modules: &HashMap<ModuleName, Module>, mut bit: ModuleName) -> u64 {
+    let mut period = 0u64;
+
+    // Walk the graph until we reach the leaf flipflop, setting a bit on the period for each flipflop that must be ON
+    // for the counter to reset.
+    loop {
+        let children = &*modules[&bit].destinations;
+
+        let should_count = children.iter().any(|c| modules[c].is_nand());
+        if should_count {
+            period |= 1;
+        }
+
+        if let Some(&next) = children.iter().find(|&c| modules[c].is_flipflop()) {
+            period <<= 1;
+            bit = next;
+        } else {
+            break;
+        }
+    }
+
+    // Due to how we iterated, we'll actually have the bits in reverse order, so we need to reverse them as a 12-bit
+    // integer.
+    period.reverse_bits() >> period.leading_zeros()
+}
+
+/// Check that the broadcaster starts the counters part 2 expects, each a chain of flipflops wired to a NAND gate that
+/// resets it.
+fn check_counters(modules: &HashMap<ModuleName, Module>) -> Result<(), BrokenAssumption> {
+    let Some(broadcaster) = modules.get(&BROADCASTER) else {
+        return Err(BrokenAssumption::new("there should be a broadcaster"));
+    };
+    if broadcaster.destinations.len() != COUNTERS {
+        return Err(BrokenAssumption::new(format!(
+            "the broadcaster should start {COUNTERS} counters"
+        )));
+    }
+
+    for &first_bit in broadcaster.destinations.iter() {
+        let mut bit = first_bit;
+        let mut gate = None;
+        for bits in 1.. {
+            if !modules.get(&bit).is_some_and(Module::is_flipflop) {
+                return Err(BrokenAssumption::new("each counter should be a chain of flipflops"));
+            }
+            let destinations = &modules[&bit].destinations;
+            if !destinations.iter().all(|c| modules.contains_key(c)) {
+                return Err(BrokenAssumption::new(
+                    "each counter should only send pulses to other modules",
+                ));
+            }
+
+            let mut gates = destinations.iter().filter(|c| modules[c].is_nand());
+            if let Some(&this_gate) = gates.next() {
+                if gates.next().is_some() || *gate.get_or_insert(this_gate) != this_gate {
+                    return Err(BrokenAssumption::new(
+                        "each counter should be wired to a single NAND gate",
+                    ));
                 }
+            }
 
-                if let Some(next) = children.iter().find(|&c| modules[c].is_flipflop()) {
-                    period <<= 1;
-                    bit = next;
-                } else {
-                    break;
+            let mut next_bits = destinations.iter().filter(|c| modules[c].is_flipflop());
+            match (next_bits.next(), next_bits.next()) {
+                (Some(&next), None) if bits < COUNTER_BITS => bit = next,
+                (None, None) if bits == COUNTER_BITS => break,
+                _ => {
+                    return Err(BrokenAssumption::new(format!(
+                        "each counter should be a chain of {COUNTER_BITS} flipflops"
+                    )))
                 }
             }
+        }
+        if gate.is_none() {
+            return Err(BrokenAssumption::new("each counter should be wired to a NAND gate"));
+        }
+    }
 
-            // Due to how we iterated, we'll actually have the bits in reverse order, so we need to reverse them as a
-            // 12-bit integer.
-            period.reverse_bits() >> period.leading_zeros()
-        })
-        .product::<u64>()
+    Ok(())
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let mut modules = include_str!("input.txt")
-        .lines()
-        .map(Module::parse)
-        .collect::<HashMap<_, _>>();
-
-    // Each NAND gate needs to know which inputs are connected to it, so we'll add that information to the graph.
-    let mut nand_inputs = HashMap::<ModuleName, Vec<ModuleName>>::default();
-    for (&name, module) in &modules {
-        for &destination in module.destinations.iter() {
-            if modules.get(&destination).map_or(false, |m| m.is_nand()) {
-                nand_inputs.entry(destination).or_default().push(name);
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = HashMap<ModuleName, Module>;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let mut modules = input
+            .lines()
+            .map(Module::parse)
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|err| err.locate(input))?;
+
+        // Each NAND gate needs to know which inputs are connected to it, so we'll add that information to the graph.
+        let mut nand_inputs = HashMap::<ModuleName, Vec<ModuleName>>::default();
+        for (&name, module) in &modules {
+            for &destination in module.destinations.iter() {
+                if modules.get(&destination).is_some_and(|m| m.is_nand()) {
+                    nand_inputs.entry(destination).or_default().push(name);
+                }
             }
         }
-    }
-    for (name, inputs) in nand_inputs {
-        let module = modules.get_mut(&name).unwrap();
-        if let ModuleType::Nand(ref mut state) = module.ty {
-            for input in inputs {
-                state.insert(input, false);
+        for (name, inputs) in nand_inputs {
+            let module = modules.get_mut(&name).unwrap();
+            if let ModuleType::Nand(ref mut state) = module.ty {
+                for input in inputs {
+                    state.insert(input, false);
+                }
             }
         }
+
+        Ok(modules)
+    }
+
+    fn part1(modules: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(solve_part1(modules.clone()))
     }
 
-    let part2 = solve_part2(&modules);
+    fn part2(modules: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(solve_part2(modules))
+    }
+
+    fn check_assumptions(modules: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        check_counters(modules)
+    }
+}
 
-    (solve_part1(modules), part2)
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("32000000"), None);
+    }
+
+    #[test]
+    fn more_interesting_example() {
+        check_example::<Puzzle>(include_str!("sample_input_2.txt"), Some("11687500"), None);
+    }
+
+    #[test]
+    fn examples_break_assumptions() {
+        let modules = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert!(Puzzle::check_assumptions(&modules).is_err());
+    }
 }
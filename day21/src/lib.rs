@@ -1,89 +1,196 @@
 use std::fmt::Display;
 
-use ::bucket_queue::{BucketQueue, LastInFirstOutQueue as _, Queue as _};
-use hibitset::BitSet;
-use rustc_hash::FxHashSet as HashSet;
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+use aoc_grid::{Grid, Pos};
+use aoc_pathfinding::{bfs, Graph};
 
 const P1_TOTAL_STEPS: usize = 64;
 const P2_TOTAL_STEPS: usize = 26_501_365;
 
+type Garden = (Grid<bool>, Pos);
+
 // https://github.com/villuna/aoc23/wiki/A-Geometric-solution-to-advent-of-code-2023,-day-21
-fn load_input(input: &str) -> (BitSet, usize, (usize, usize)) {
-    let side = input.lines().next().unwrap().len();
+fn load_input(input: &str) -> Result<Garden, ParseError> {
     let mut start_pos = None;
-    let map = input
-        .lines()
-        .flat_map(|line| line.bytes())
-        .enumerate()
-        .filter_map(|(idx, b)| match b {
-            b'S' => {
-                start_pos = Some((idx / side, idx % side));
-                None
-            }
-            b'#' => Some(idx as u32),
-            b'.' => None,
-            _ => panic!(),
-        })
-        .collect();
-    let start_pos = start_pos.unwrap();
-    (map, side, start_pos)
+    let walls = Grid::parse(input, |pos, b| match b {
+        b'S' => {
+            start_pos = Some(pos);
+            Ok(false)
+        }
+        b'#' => Ok(true),
+        b'.' => Ok(false),
+        _ => Err("expected a plot '.' or a rock '#'"),
+    })
+    .map_err(|err| err.locate(input))?;
+    if walls.height() != walls.width() {
+        let reason = format!("expected a square garden of {} rows", walls.width());
+        return Err(Unexpected::new("", reason).locate(input));
+    }
+    let start_pos = start_pos.ok_or_else(|| Unexpected::new("", "no starting position 'S'").locate(input))?;
+    Ok((walls, start_pos))
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let (walls, side, start_pos) = load_input(input);
+#[derive(Default)]
+struct Walk {
+    part1: usize,
+    odd_full: usize,
+    even_full: usize,
+    odd_corners: usize,
+    even_corners: usize,
+}
 
-    let mut q = BucketQueue::<Vec<_>>::new();
-    q.push(start_pos, 0);
+/// The plots of the garden, and the ways between them.
+struct Plots<'a>(&'a Grid<bool>);
 
-    let mut visited = HashSet::default();
+impl Graph for Plots<'_> {
+    type Node = Pos;
 
-    let mut part1 = 0;
+    fn node_count(&self) -> usize {
+        self.0.cells().len()
+    }
 
-    let mut odd_full = 0;
-    let mut even_full = 0;
-    let mut odd_corners = 0;
-    let mut even_corners = 0;
+    fn index(&self, pos: Pos) -> usize {
+        self.0.index_of(pos)
+    }
 
-    while let Some(distance) = q.min_priority() {
-        let (x, y) = q.pop(distance).unwrap();
-        if !visited.insert((x, y)) {
-            continue;
-        }
+    fn successors(&self, pos: Pos, mut edge: impl FnMut(Pos, usize)) {
+        self.0
+            .neighbors4(pos)
+            .filter(|&next| !self.0[next])
+            .for_each(|next| edge(next, 1));
+    }
+}
+
+/// Walk every reachable plot of the garden, counting the ones the elf can end up on in `steps` steps for part 1 along
+/// the way.
+fn walk((walls, start_pos): &Garden, steps: usize) -> Walk {
+    let side = walls.width();
+    let mut walk = Walk::default();
 
-        if distance < P1_TOTAL_STEPS {
-            part1 += 1;
+    for (_, distance) in bfs(&Plots(walls), [*start_pos], |_| false).settled() {
+        if distance <= steps && distance % 2 == steps % 2 {
+            walk.part1 += 1;
         }
 
         if distance % 2 == 0 {
-            even_full += 1;
-            if distance > 65 {
-                even_corners += 1;
+            walk.even_full += 1;
+            if distance > side / 2 {
+                walk.even_corners += 1;
             }
         } else {
-            odd_full += 1;
-            if distance > 65 {
-                odd_corners += 1;
+            walk.odd_full += 1;
+            if distance > side / 2 {
+                walk.odd_corners += 1;
             }
         }
+    }
+
+    walk
+}
+
+/// Check the garden has the shape the geometric solution relies on: the elf starts right in the middle of it, with
+/// nothing in the way of walking straight to its edges and then along them, and walks just far enough to end up on the
+/// edge of a far-off copy of it.
+fn check_garden((walls, start_pos): &Garden) -> Result<(), BrokenAssumption> {
+    let side = walls.width();
+    if side % 2 == 0 || *start_pos != (side / 2, side / 2) {
+        return Err(BrokenAssumption::new(
+            "the elf should start in the very middle of the garden",
+        ));
+    }
+    if P2_TOTAL_STEPS
+        .checked_sub(side / 2)
+        .is_none_or(|steps| steps % side != 0)
+    {
+        return Err(BrokenAssumption::new(format!(
+            "walking {P2_TOTAL_STEPS} steps from the middle of the garden should end on the edge of another copy of it"
+        )));
+    }
 
-        let neighbors = [
-            (x.checked_sub(1), Some(y)),
-            (Some(x + 1), Some(y)),
-            (Some(x), y.checked_sub(1)),
-            (Some(x), Some(y + 1)),
-        ];
-        neighbors
-            .into_iter()
-            .filter_map(|(x, y)| x.zip(y))
-            .filter(|&(x, y)| x < side && y < side)
-            .filter(|&(x, y)| !walls.contains((y * side + x) as u32))
-            .for_each(|pos| q.push(pos, distance + 1))
+    let is_clear = |pos: Pos| !walls[pos];
+    let middle = side / 2;
+    for i in 0..side {
+        if !is_clear((middle, i)) || !is_clear((i, middle)) {
+            return Err(BrokenAssumption::new(
+                "the middle row and column of the garden should be clear",
+            ));
+        }
+        if ![(0, i), (side - 1, i), (i, 0), (i, side - 1)].into_iter().all(is_clear) {
+            return Err(BrokenAssumption::new("the edges of the garden should be clear"));
+        }
     }
 
+    Ok(())
+}
+
+fn extrapolate(side: usize, walk: &Walk) -> usize {
+    let Walk {
+        odd_full,
+        even_full,
+        odd_corners,
+        even_corners,
+        ..
+    } = *walk;
     let n = (P2_TOTAL_STEPS - (side / 2)) / side;
-    let part2 = ((n + 1) * (n + 1)) * odd_full + (n * n) * even_full - (n + 1) * odd_corners + n * even_corners - n;
+    ((n + 1) * (n + 1)) * odd_full + (n * n) * even_full - (n + 1) * odd_corners + n * even_corners - n
+}
+
+pub struct Puzzle;
 
-    (part1, part2)
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Garden;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        load_input(input)
+    }
+
+    fn part1(garden: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(walk(garden, P1_TOTAL_STEPS).part1)
+    }
+
+    fn part2(garden: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(extrapolate(garden.0.width(), &walk(garden, P1_TOTAL_STEPS)))
+    }
+
+    fn check_assumptions(garden: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        check_garden(garden)
+    }
+
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), InputError> {
+        let garden = Self::parse(input)?;
+        Self::check_assumptions(&garden)?;
+        let walk = walk(&garden, P1_TOTAL_STEPS);
+        Ok((walk.part1, extrapolate(garden.0.width(), &walk)))
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let garden = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(walk(&garden, 6).part1, 16);
+    }
+
+    #[test]
+    fn example_breaks_assumptions() {
+        let garden = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert!(Puzzle::check_assumptions(&garden).is_err());
+    }
 }
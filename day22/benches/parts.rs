@@ -11,7 +11,8 @@ fn parts(c: &mut Criterion) {
                         .lines()
                         .zip(0..)
                         .map(|(line, id)| Brick::parse(id, line))
-                        .collect::<Vec<_>>(),
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap(),
                 )
             },
             |mut bricks| simulate_until_settled(&mut bricks),
@@ -1,15 +1,20 @@
 use std::fmt::{Debug, Display};
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
 use arrayvec::ArrayVec;
 use hibitset::{BitSet, BitSetLike};
 use itertools::iproduct;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use rustc_hash::FxHashSet as HashSet;
 
 const SIDE: usize = 10;
 
-type SupportTree = Vec<ArrayVec<usize, 3>>;
+/// How many bricks another one can rest on at most.
+const SUPPORTERS: usize = 3;
+
+type SupportTree = Vec<ArrayVec<usize, SUPPORTERS>>;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Pos {
@@ -29,13 +34,18 @@ impl Debug for Pos {
 }
 
 impl Pos {
-    fn parse(s: &str) -> Self {
-        let mut it = s.split(',').map(|s| s.parse().unwrap());
-        Self {
-            x: it.next().unwrap(),
-            y: it.next().unwrap(),
-            z: it.next().unwrap(),
+    fn parse(s: &str) -> Result<Self, Unexpected<'_>> {
+        let coordinates = s.split(',').map(str::try_parse).collect::<Result<Vec<_>, _>>()?;
+        let &[x, y, z] = coordinates.as_slice() else {
+            return Err(Unexpected::new(s, "expected three coordinates"));
+        };
+        if usize::from(x) >= SIDE || usize::from(y) >= SIDE {
+            return Err(Unexpected::new(
+                s,
+                format!("bricks must lie within a {SIDE}x{SIDE} area"),
+            ));
         }
+        Ok(Self { x, y, z })
     }
 }
 
@@ -53,72 +63,129 @@ impl Debug for Brick {
 }
 
 impl Brick {
-    pub fn parse(id: usize, s: &str) -> Self {
-        let (start, end) = s.split_once('~').unwrap();
-        let start = Pos::parse(start);
-        let end = Pos::parse(end);
-        let (start, end) = if start.x > end.x || start.y > end.y || start.z > end.z {
-            (end, start)
-        } else {
-            (start, end)
+    pub fn parse(id: usize, s: &str) -> Result<Self, Unexpected<'_>> {
+        let (start, end) = s.try_split_once("~")?;
+        let (a, b) = (Pos::parse(start)?, Pos::parse(end)?);
+        let start = Pos {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+            z: a.z.min(b.z),
+        };
+        let end = Pos {
+            x: a.x.max(b.x),
+            y: a.y.max(b.y),
+            z: a.z.max(b.z),
         };
-        Self { id, start, end }
+        Ok(Self { id, start, end })
     }
 }
 
-#[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let mut bricks = include_str!("input.txt")
-        .lines()
-        .zip(0..)
-        .map(|(line, id)| Brick::parse(id, line))
-        .collect::<Vec<_>>();
-    simulate_until_settled(&mut bricks);
-
-    let supporters = get_support_tree(&bricks);
-
-    bricks.sort_unstable_by_key(|brick| brick.id);
+pub struct Tower {
+    bricks: Vec<Brick>,
+    supporters: SupportTree,
+}
 
+fn sole_supporters(supporters: &SupportTree) -> HashSet<usize> {
     let mut sole_supporters = HashSet::default();
-    for supported_by in &supporters {
+    for supported_by in supporters {
         if let [sole_supporter] = supported_by.as_slice() {
             sole_supporters.insert(*sole_supporter);
         }
     }
-    let p1 = bricks.len() - sole_supporters.len();
+    sole_supporters
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
 
-    let mut supportees = vec![ArrayVec::<_, 4>::new(); bricks.len()];
-    for (&supportee, supported_by) in bricks.iter().zip(supporters.iter()) {
-        for &supporter in supported_by {
-            supportees[supporter].push(supportee.id);
+    /// The bricks once they've settled, sorted by id, along with which bricks support each one.
+    type Input<'a> = Tower;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let mut bricks = input
+            .lines()
+            .zip(0..)
+            .map(|(line, id)| Brick::parse(id, line))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.locate(input))?;
+
+        // Settled bricks are stacked no higher than they'd be piled right on top of each other.
+        let pile_height = bricks
+            .iter()
+            .map(|brick| u32::from(brick.end.z - brick.start.z) + 1)
+            .sum::<u32>();
+        if pile_height >= u32::from(u16::MAX) {
+            let reason = format!("the bricks could pile up higher than {}", u16::MAX);
+            return Err(Unexpected::new("", reason).locate(input));
         }
+
+        simulate_until_settled(&mut bricks);
+
+        let supporters = get_support_tree(&bricks).map_err(|id| {
+            let line = input.lines().nth(id).unwrap_or_default();
+            let reason = format!("a brick should rest on at most {SUPPORTERS} others");
+            Unexpected::new(line, reason).locate(input)
+        })?;
+
+        bricks.sort_unstable_by_key(|brick| brick.id);
+
+        Ok(Tower { bricks, supporters })
+    }
+
+    fn part1(Tower { bricks, supporters }: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(bricks.len() - sole_supporters(supporters).len())
     }
 
-    let p2 = sole_supporters
-        .into_par_iter()
-        .map(|brick| {
-            let mut falling = BitSet::new();
-            falling.add(brick as u32);
-
-            let mut q = vec![brick];
-            while let Some(brick) = q.pop() {
-                let children = &supportees[brick];
-                for &child in children {
-                    if supporters[child]
-                        .iter()
-                        .all(|&support| falling.contains(support as u32))
-                    {
-                        falling.add(child as u32);
-                        q.push(child);
+    fn part2(Tower { bricks, supporters }: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        let mut supportees = vec![ArrayVec::<_, 4>::new(); bricks.len()];
+        for (&supportee, supported_by) in bricks.iter().zip(supporters.iter()) {
+            for &supporter in supported_by {
+                supportees[supporter].push(supportee.id);
+            }
+        }
+
+        let sole_supporters = sole_supporters(supporters);
+        #[cfg(feature = "parallel")]
+        let sole_supporters = sole_supporters.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let sole_supporters = sole_supporters.into_iter();
+        Ok(sole_supporters
+            .map(|brick| {
+                let mut falling = BitSet::new();
+                falling.add(brick as u32);
+
+                let mut q = vec![brick];
+                while let Some(brick) = q.pop() {
+                    let children = &supportees[brick];
+                    for &child in children {
+                        if supporters[child]
+                            .iter()
+                            .all(|&support| falling.contains(support as u32))
+                        {
+                            falling.add(child as u32);
+                            q.push(child);
+                        }
                     }
                 }
-            }
 
-            falling.iter().count() - 1
-        })
-        .sum::<usize>();
+                falling.iter().count() - 1
+            })
+            .sum::<usize>())
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
 
-    (p1, p2)
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
 }
 
 pub fn simulate_until_settled(bricks: &mut Vec<Brick>) {
@@ -144,24 +211,36 @@ fn intersects_xy(a: &Brick, b: &Brick) -> bool {
     a.start.x <= b.end.x && a.end.x >= b.start.x && a.start.y <= b.end.y && a.end.y >= b.start.y
 }
 
-fn get_support_tree(bricks: &[Brick]) -> SupportTree {
+/// Find which bricks each brick rests on, or the id of one that rests on more than [`SUPPORTERS`] others.
+fn get_support_tree(bricks: &[Brick]) -> Result<SupportTree, usize> {
     let mut tree = vec![ArrayVec::new(); bricks.len()];
-    (0..bricks.len()).for_each(|idx| {
-        let brick = &bricks[idx];
-
+    for (idx, brick) in bricks.iter().enumerate() {
         if brick.start.z == 0 {
-            return;
+            continue;
         }
 
         let target_z = brick.start.z - 1;
 
-        tree[brick.id] = bricks
+        for support in bricks
             .iter()
             .take(idx)
             .filter(|support| support.end.z == target_z)
             .filter(|support| intersects_xy(brick, support))
-            .map(|support| support.id)
-            .collect();
-    });
-    tree
+        {
+            tree[brick.id].try_push(support.id).map_err(|_| brick.id)?;
+        }
+    }
+    Ok(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("5"), Some("7"));
+    }
 }
@@ -1,17 +1,17 @@
+use aoc_common::Solution;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day23::Puzzle;
 
 fn parts(c: &mut Criterion) {
+    let map = Puzzle::parse(Puzzle::INPUT).unwrap();
+
     c.bench_function("part1", |b| {
-        b.iter(|| day23::part1::solve(black_box(include_str!("../src/input.txt"))));
+        b.iter(|| day23::part1::solve(black_box(&map)));
     });
 
-    c.bench_with_input(
-        criterion::BenchmarkId::new("part2", "input.txt"),
-        &include_str!("../src/input.txt").replace(['>', '<', '^', 'v'], "."),
-        |b, i| {
-            b.iter(|| day23::part2::solve(black_box(i)));
-        },
-    );
+    c.bench_function("part2", |b| {
+        b.iter(|| day23::part2::solve(black_box(&map)));
+    });
 }
 
 criterion_group!(benches, parts);
@@ -1,10 +1,119 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, Solution, Unexpected};
+use aoc_grid::{Direction, Grid};
+use aoc_pathfinding::{bfs, Graph};
+
 pub mod part1;
 pub mod part2;
 
+/// The map as the slopes let you walk it, to check the way out can be reached at all.
+struct Hike<'a>(&'a Grid<u8>);
+
+impl Graph for Hike<'_> {
+    type Node = usize;
+
+    fn node_count(&self) -> usize {
+        self.0.cells().len()
+    }
+
+    fn index(&self, idx: usize) -> usize {
+        idx
+    }
+
+    fn successors(&self, idx: usize, mut edge: impl FnMut(usize, usize)) {
+        let directions: &[Direction] = match self.0.cells()[idx] {
+            b'>' => &[Direction::Right],
+            b'v' => &[Direction::Down],
+            b'<' => &[Direction::Left],
+            b'^' => &[Direction::Up],
+            _ => &Direction::ALL,
+        };
+        directions
+            .iter()
+            .filter_map(|&dir| self.0.step_index(idx, dir))
+            .filter(|&next| self.0.cells()[next] != b'#')
+            .for_each(|next| edge(next, 1));
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Grid<u8>;
+    type Part1 = u16;
+    type Part2 = u16;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let map = Grid::parse(input, |_, b| match b {
+            b'.' | b'#' | b'>' | b'v' | b'<' | b'^' => Ok(b),
+            _ => Err("expected a path, a forest or a slope"),
+        })
+        .map_err(|err| err.locate(input))?;
+        // Positions are stored as a pair of `u8`s.
+        if !(2..=256).contains(&map.width()) || !(2..=256).contains(&map.height()) {
+            return Err(Unexpected::new(input, "expected a map between 2 and 256 tiles on each side").locate(input));
+        }
+        for (y, row) in input.lines().enumerate() {
+            if (y == 0 || y == map.height() - 1) && row.matches('.').count() != 1 {
+                return Err(Unexpected::new(row, "expected exactly one opening in the outer wall").locate(input));
+            }
+        }
+        Ok(map)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(part1::solve(input))
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(part2::solve(input))
+    }
+
+    fn check_assumptions(map: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        // `parse` made sure there's exactly one opening in the top and bottom rows.
+        let opening = |y| map.index_of((y, 0)) + map.row(y).iter().position(|&b| b == b'.').unwrap();
+        let (start, goal) = (opening(0), opening(map.height() - 1));
+        if bfs(&Hike(map), [start], |idx| idx == goal).goal().is_none() {
+            return Err(BrokenAssumption::new(
+                "there should be a way down the slopes from the start to the end",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    (part1::solve(input), part2::solve(input))
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), Some("94"), Some("154"));
+    }
+
+    #[test]
+    fn unreachable_ends_break_assumptions() {
+        let map = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&map), Ok(()));
+
+        let map = Puzzle::parse("#.###\n#.###\n#.###\n#.###\n###.#").unwrap();
+        assert!(Puzzle::check_assumptions(&map).is_err());
+        let map = Puzzle::parse("#.###\n#^###\n#.###\n#.###\n#.###").unwrap();
+        assert!(Puzzle::check_assumptions(&map).is_err());
+    }
 }
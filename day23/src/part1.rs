@@ -1,3 +1,4 @@
+use aoc_grid::{Direction, Grid, Pos};
 use petgraph::prelude::*;
 
 type CondensedGraph = DiGraph<(u8, u8), u16, u16>;
@@ -21,70 +22,39 @@ fn do_search(
         .unwrap_or(0)
 }
 
-pub fn solve(input: &str) -> u16 {
+pub fn solve(grid: &Grid<u8>) -> u16 {
     let mut graph = DiGraphMap::<(u8, u8), u16>::new();
 
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
-    let grid = grid::Grid::from_vec(input.bytes().filter(|&b| !b.is_ascii_whitespace()).collect(), width);
+    let node = |(y, x): Pos| (x as u8, y as u8);
     let mut start = None;
     let mut goal = None;
 
-    for (y, row) in grid.iter_rows().enumerate() {
-        for (x, c) in row.enumerate() {
-            let dot_neighbors: [_; 4];
-            let arrow_neighbor;
-
-            let neighbors = match c {
-                b'.' => {
-                    dot_neighbors = [
-                        y.checked_sub(1).map(|y| (x, y)),
-                        x.checked_sub(1).map(|x| (x, y)),
-                        Some((x + 1, y)),
-                        Some((x, y + 1)),
-                    ];
-
-                    if y == 0 {
-                        start = Some((x as u8, y as u8));
-                    } else if y == height - 1 {
-                        goal = Some((x as u8, y as u8));
-                    }
-
-                    &dot_neighbors[..]
-                }
-
-                b'>' => {
-                    arrow_neighbor = Some((x + 1, y));
-                    std::array::from_ref(&arrow_neighbor)
-                }
-
-                b'v' => {
-                    arrow_neighbor = Some((x, y + 1));
-                    std::array::from_ref(&arrow_neighbor)
-                }
-
-                b'<' => {
-                    arrow_neighbor = y.checked_sub(1).map(|y| (x, y));
-                    std::array::from_ref(&arrow_neighbor)
+    for pos in grid.positions() {
+        let neighbors: &[Direction] = match grid[pos] {
+            b'.' => {
+                if pos.0 == 0 {
+                    start = Some(node(pos));
+                } else if pos.0 == grid.height() - 1 {
+                    goal = Some(node(pos));
                 }
 
-                b'^' => {
-                    arrow_neighbor = y.checked_sub(1).map(|y| (x, y));
-                    std::array::from_ref(&arrow_neighbor)
-                }
+                &Direction::ALL
+            }
 
-                b'#' => continue,
+            b'>' => &[Direction::Right],
+            b'v' => &[Direction::Down],
+            b'<' => &[Direction::Left],
+            b'^' => &[Direction::Up],
 
-                _ => panic!("Unknown character: {}", *c as char),
-            };
+            _ => continue,
+        };
 
-            for (nx, ny) in neighbors
-                .iter()
-                .filter_map(|&n| n)
-                .filter(|&(nx, ny)| !matches!(grid.get(ny, nx), None | Some(b'#')))
-            {
-                graph.add_edge((x as u8, y as u8), (nx as u8, ny as u8), 1);
-            }
+        for next in neighbors
+            .iter()
+            .filter_map(|&dir| grid.step(pos, dir))
+            .filter(|&next| grid[next] != b'#')
+        {
+            graph.add_edge(node(pos), node(next), 1);
         }
     }
     let start = start.unwrap();
@@ -1,5 +1,7 @@
 use std::{collections::VecDeque, mem::swap};
 
+use aoc_grid::{Direction, Grid};
+use aoc_pathfinding::{bfs, Graph};
 use arrayvec::ArrayVec;
 use rustc_hash::FxHashMap as HashMap;
 
@@ -137,19 +139,68 @@ impl Solver {
     }
 }
 
-pub fn solve(input: &str) -> Cost {
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
-    let area = width * height;
+/// Walking along the trails from `source`, up to the next junctions.
+struct Trails<'a> {
+    adjacency: &'a [ArrayVec<usize, 4>],
+    source: usize,
+    start_idx: usize,
+    goal_idx: usize,
+}
 
-    let start_idx = 1;
-    let goal_idx = area - 2;
+impl Trails<'_> {
+    fn is_junction(&self, node: usize) -> bool {
+        self.adjacency[node].len() > 2 || node == self.start_idx || node == self.goal_idx
+    }
+}
 
-    let occupancy = input
-        .bytes()
-        .filter(|&b| b != b'\n')
-        .map(|b| b != b'#')
-        .collect::<Vec<_>>();
+impl Graph for Trails<'_> {
+    type Node = usize;
+
+    fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn index(&self, node: usize) -> usize {
+        node
+    }
+
+    fn successors(&self, node: usize, mut edge: impl FnMut(usize, usize)) {
+        if node == self.source || !self.is_junction(node) {
+            self.adjacency[node].iter().for_each(|&neighbor| edge(neighbor, 1));
+        }
+    }
+}
+
+/// The junctions, and the lengths of the trails between them.
+struct Junctions<'a>(&'a [ArrayVec<(Node, Cost), 4>]);
+
+impl Graph for Junctions<'_> {
+    type Node = Node;
+
+    fn node_count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn index(&self, node: Node) -> usize {
+        usize::from(node)
+    }
+
+    fn successors(&self, node: Node, mut edge: impl FnMut(Node, usize)) {
+        self.0[usize::from(node)]
+            .iter()
+            .for_each(|&(neighbor, weight)| edge(neighbor, usize::from(weight)));
+    }
+}
+
+pub fn solve(grid: &Grid<u8>) -> Cost {
+    let area = grid.cells().len();
+
+    // The map was checked to have exactly one opening in its top and bottom rows.
+    let start_idx = grid.row(0).iter().position(|&b| b == b'.').unwrap();
+    let goal_idx =
+        grid.index_of((grid.height() - 1, 0)) + grid.row(grid.height() - 1).iter().position(|&b| b == b'.').unwrap();
+
+    let occupancy = grid.iter().map(|&b| b != b'#').collect::<Vec<_>>();
 
     let adjacency = occupancy
         .iter()
@@ -159,23 +210,11 @@ pub fn solve(input: &str) -> Cost {
                 return ArrayVec::<usize, 4>::new();
             }
 
-            let mut neighbors = ArrayVec::new();
-
-            let x = idx % width;
-            let y = idx / width;
-
-            if x != 0 && occupancy[idx - 1] {
-                neighbors.push(idx - 1);
-            }
-            if y != 0 && occupancy[idx - width] {
-                neighbors.push(idx - width);
-            }
-            if x != width - 1 && occupancy[idx + 1] {
-                neighbors.push(idx + 1);
-            }
-            if y != height - 1 && occupancy[idx + width] {
-                neighbors.push(idx + width);
-            }
+            let mut neighbors = Direction::ALL
+                .into_iter()
+                .filter_map(|dir| grid.step_index(idx, dir))
+                .filter(|&neighbor| occupancy[neighbor])
+                .collect::<ArrayVec<_, 4>>();
             neighbors.sort();
 
             neighbors
@@ -189,26 +228,19 @@ pub fn solve(input: &str) -> Cost {
     outer_queue.push_back(start_idx);
 
     while let Some(source) = outer_queue.pop_front() {
-        let mut visited = vec![false; area];
-        let mut queue: VecDeque<(usize, Cost)> = Default::default();
-        queue.push_back((source, 0));
-
-        while let Some((node, cost)) = queue.pop_front() {
-            let neighbors = &adjacency[node];
-
-            if (neighbors.len() > 2 || node == start_idx || node == goal_idx) && node != source {
-                graph[source].push((node, cost));
+        let trails = Trails {
+            adjacency: &adjacency,
+            source,
+            start_idx,
+            goal_idx,
+        };
+        for (node, cost) in bfs(&trails, [source], |_| false).settled() {
+            if node != source && trails.is_junction(node) {
+                graph[source].push((node, cost as Cost));
                 if !outer_visited[node] {
                     outer_visited[node] = true;
                     outer_queue.push_back(node);
                 }
-            } else {
-                for &neighbor in neighbors {
-                    if !visited[neighbor] {
-                        visited[neighbor] = true;
-                        queue.push_back((neighbor, cost + 1));
-                    }
-                }
             }
         }
     }
@@ -244,15 +276,10 @@ pub fn solve(input: &str) -> Cost {
 
     let mut solver = Solver::new(start_idx, goal_idx);
 
-    let mut q = VecDeque::new();
-    let mut visited = vec![false; area];
-    let mut known = vec![false; area];
-    q.push_back(start_idx);
-    visited[usize::from(start_idx)] = true;
-
+    let mut known = vec![false; graph.len()];
     let mut unexplored = graph.iter().map(|neighbors| neighbors.len()).collect::<Vec<_>>();
 
-    while let Some(node) = q.pop_front() {
+    for (node, _) in bfs(&Junctions(&graph), [start_idx], |_| false).settled() {
         solver.add_vertex(node);
         known[usize::from(node)] = true;
 
@@ -267,9 +294,6 @@ pub fn solve(input: &str) -> Cost {
                 if unexplored[usize::from(neighbor)] == 0 {
                     solver.remove_vertex(neighbor);
                 }
-            } else if !visited[usize::from(neighbor)] {
-                visited[usize::from(neighbor)] = true;
-                q.push_back(neighbor);
             }
         }
     }
@@ -1,8 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::RangeInclusive};
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
 use nalgebra::{Matrix4, RowVector4, Vector4};
 
-struct Hailstone {
+pub struct Hailstone {
     position: [f64; 3],
     velocity: [f64; 3],
 }
@@ -18,14 +19,22 @@ impl std::fmt::Debug for Hailstone {
 }
 
 impl Hailstone {
-    fn parse(s: &str) -> Self {
-        let (p, v) = s.split_once(" @ ").unwrap();
-        let mut p = p.split(", ").map(|n| n.trim().parse().unwrap());
-        let mut v = v.split(", ").map(|n| n.trim().parse().unwrap());
-        Self {
-            position: [p.next().unwrap(), p.next().unwrap(), p.next().unwrap()],
-            velocity: [v.next().unwrap(), v.next().unwrap(), v.next().unwrap()],
+    fn parse(s: &str) -> Result<Self, Unexpected<'_>> {
+        fn parse_vector(s: &str) -> Result<[f64; 3], Unexpected<'_>> {
+            let components = s
+                .split(", ")
+                .map(|n| n.trim().try_parse())
+                .collect::<Result<Vec<_>, _>>()?;
+            components
+                .try_into()
+                .map_err(|_| Unexpected::new(s, "expected three components"))
         }
+
+        let (p, v) = s.try_split_once(" @ ")?;
+        Ok(Self {
+            position: parse_vector(p)?,
+            velocity: parse_vector(v)?,
+        })
     }
 
     fn time_for(&self, coord: usize, target: f64) -> f64 {
@@ -47,11 +56,9 @@ impl Hailstone {
     }
 }
 
-fn in_test_area(coord: f64) -> bool {
-    (200000000000000. ..=400000000000000.).contains(&coord)
-}
+const TEST_AREA: RangeInclusive<f64> = 200000000000000. ..=400000000000000.;
 
-fn do_part1(hailstones: &[Hailstone]) -> i32 {
+fn do_part1(hailstones: &[Hailstone], test_area: RangeInclusive<f64>) -> i32 {
     let mut part1 = 0;
     for (i, h1) in hailstones.iter().enumerate() {
         for h2 in hailstones.iter().skip(i + 1) {
@@ -60,7 +67,7 @@ fn do_part1(hailstones: &[Hailstone]) -> i32 {
                 continue;
             }
 
-            if in_test_area(x) && in_test_area(y) {
+            if test_area.contains(&x) && test_area.contains(&y) {
                 part1 += 1;
             }
         }
@@ -100,22 +107,132 @@ fn find_z_dz(x: f64, dx: f64, h0: &Hailstone, h1: &Hailstone) -> (f64, f64) {
     (z, dz)
 }
 
-fn do_part2(hs: &[Hailstone; 5]) -> f64 {
+/// Find the position and velocity of the rock that hits the five hailstones, if they pin a single one down.
+fn find_rock(hs: &[Hailstone; 5]) -> Option<([f64; 3], [f64; 3])> {
     let (head, tail) = hs.split_first().unwrap();
     let sol = {
         let (m, r) = to_xy_equations(head, tail);
-        m.lu().solve(&r).unwrap()
+        m.lu().solve(&r)?
     };
     let x = sol[0];
     let dx = sol[1];
     let y = sol[2];
-    let (z, _) = find_z_dz(x, dx, head, &tail[0]);
-    x + y + z
+    let dy = sol[3];
+    let (z, dz) = find_z_dz(x, dx, head, &tail[0]);
+    Some(([x, y, z], [dx, dy, dz]))
+}
+
+fn do_part2(hs: &[Hailstone; 5]) -> i64 {
+    let ([x, y, z], _) = find_rock(hs).unwrap();
+    // The rock starts on integer coordinates; round away the error solving the system accumulated.
+    (x + y + z).round() as i64
+}
+
+/// Check that the rock part 2 throws to hit the first five hailstones hits all the others too, each at a whole number
+/// of nanoseconds after it's thrown.
+fn check_rock(hailstones: &[Hailstone]) -> Result<(), BrokenAssumption> {
+    let Some((position, velocity)) = find_rock(hailstones[..5].try_into().unwrap()) else {
+        return Err(BrokenAssumption::new(
+            "the first five hailstones should only be hit by throwing the rock one way",
+        ));
+    };
+
+    let round = |v: [f64; 3]| v.map(|c| c.round() as i128);
+    let (position, velocity) = (round(position), round(velocity));
+    for (idx, hailstone) in hailstones.iter().enumerate() {
+        let (hail_position, hail_velocity) = (round(hailstone.position), round(hailstone.velocity));
+        let mut time = None;
+        let hits = (0..3).all(|c| {
+            let distance = hail_position[c] - position[c];
+            let closing_speed = velocity[c] - hail_velocity[c];
+            if closing_speed == 0 {
+                return distance == 0;
+            }
+            distance % closing_speed == 0
+                && distance / closing_speed >= 0
+                && *time.get_or_insert(distance / closing_speed) == distance / closing_speed
+        });
+        if !hits {
+            return Err(BrokenAssumption::new(format!(
+                "the rock thrown to hit the first five hailstones should hit every other one, but misses #{}",
+                idx + 1
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Vec<Hailstone>;
+    type Part1 = i32;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        let hailstones = input
+            .lines()
+            .map(Hailstone::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.locate(input))?;
+        // The second part pins the rock's trajectory down from the first five hailstones.
+        if hailstones.len() < 5 {
+            return Err(Unexpected::new("", "expected at least five hailstones").locate(input));
+        }
+        Ok(hailstones)
+    }
+
+    fn part1(hailstones: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(do_part1(hailstones, TEST_AREA))
+    }
+
+    fn part2(hailstones: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok(do_part2(hailstones[..5].try_into().unwrap()))
+    }
+
+    fn check_assumptions(hailstones: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        check_rock(hailstones)
+    }
 }
 
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let input = include_str!("input.txt");
-    let hailstones = input.lines().map(Hailstone::parse).collect::<Vec<_>>();
-    (do_part1(&hailstones), do_part2(hailstones[..5].try_into().unwrap()))
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_common::check_example;
+
+    use super::*;
+
+    #[test]
+    fn example() {
+        check_example::<Puzzle>(include_str!("sample_input.txt"), None, Some("47"));
+    }
+
+    #[test]
+    fn part1_example() {
+        let hailstones = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(do_part1(&hailstones, 7. ..=27.), 2);
+    }
+
+    #[test]
+    fn missed_hailstone_breaks_assumptions() {
+        let hailstones = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&hailstones), Ok(()));
+
+        let input = format!("{}\n0, 0, 0 @ 1, 1, 1", include_str!("sample_input.txt").trim_end());
+        let hailstones = Puzzle::parse(&input).unwrap();
+        let err = Puzzle::check_assumptions(&hailstones).unwrap_err();
+        assert!(err.reason.ends_with("#6"), "{err}");
+    }
 }
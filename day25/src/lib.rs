@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
+use aoc_common::{BrokenAssumption, InputError, ParseError, ParseExt as _, Solution, Unexpected};
+use aoc_pathfinding::bfs;
 use rand::prelude::*;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use rustc_hash::FxHashMap as HashMap;
 
@@ -8,53 +11,112 @@ use petgraph::prelude::*;
 
 const SAMPLE_POINTS: usize = 400;
 
+type Wiring = Graph<(), (), Undirected, usize>;
+
+/// The wires between components, to find paths along.
+struct Wires<'a>(&'a Wiring);
+
+impl aoc_pathfinding::Graph for Wires<'_> {
+    type Node = NodeIndex<usize>;
+
+    fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    fn index(&self, node: Self::Node) -> usize {
+        node.index()
+    }
+
+    fn successors(&self, node: Self::Node, mut edge: impl FnMut(Self::Node, usize)) {
+        self.0.neighbors(node).for_each(|neighbor| edge(neighbor, 1));
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = Wiring;
+    type Part1 = usize;
+    type Part2 = &'static str;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        load_input(input)
+    }
+
+    fn part1(graph: &Self::Input<'_>) -> Result<Self::Part1, BrokenAssumption> {
+        Ok(do_solve(graph.clone()))
+    }
+
+    fn part2(_graph: &Self::Input<'_>) -> Result<Self::Part2, BrokenAssumption> {
+        Ok("Merry Christmas!")
+    }
+
+    fn check_assumptions(graph: &Self::Input<'_>) -> Result<(), BrokenAssumption> {
+        // Paths are sampled between any two components, so there has to be a path between any two.
+        if petgraph::algo::connected_components(graph) != 1 {
+            return Err(BrokenAssumption::new(
+                "every component should be wired up to every other",
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    let graph = load_input();
-    let answer = do_solve(graph);
-    (answer, "Merry Christmas!")
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
 }
 
-fn load_input() -> Graph<(), (), Undirected, usize> {
-    let mut graph = UnGraphMap::<&'static str, ()>::new();
-    for line in include_str!("input.txt").lines() {
-        let (source, destinations) = line.split_once(": ").unwrap();
+#[inline]
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
+}
+
+fn load_input(input: &str) -> Result<Wiring, ParseError> {
+    let mut graph = UnGraphMap::<&str, ()>::new();
+    for line in input.lines() {
+        let (source, destinations) = line.try_split_once(": ").map_err(|err| err.locate(input))?;
         for destination in destinations.split(' ') {
             graph.add_edge(source, destination, ());
         }
     }
-    graph.into_graph::<usize>().map(|_, _| (), |_, _| ())
+    if graph.node_count() == 0 {
+        return Err(Unexpected::new(input, "expected at least one component").locate(input));
+    }
+    Ok(graph.into_graph::<usize>().map(|_, _| (), |_, _| ()))
 }
 
 // adapted from https://www.reddit.com/r/adventofcode/comments/18qe8qo/2023_day_25_part_1_why_work_hard/keudy5q/
-fn do_solve(mut graph: Graph<(), (), Undirected, usize>) -> usize {
+fn do_solve(mut graph: Wiring) -> usize {
+    let sample_path = |mut frequencies: HashMap<_, usize>, _| {
+        let mut rng = thread_rng();
+        let start = NodeIndex::new(rng.gen_range(0..graph.node_count()));
+        let end = NodeIndex::new(rng.gen_range(0..graph.node_count()));
+        let path = bfs(&Wires(&graph), [start], |finish| finish == end)
+            .path_to(end)
+            .unwrap();
+        path.windows(2)
+            .map(|window| graph.edges_connecting(window[0], window[1]).next().unwrap())
+            .for_each(|edge| {
+                *frequencies.entry(edge.id()).or_default() += 1;
+            });
+        frequencies
+    };
+
+    #[cfg(feature = "parallel")]
     let frequencies = (0..SAMPLE_POINTS)
         .into_par_iter()
-        .fold(
-            HashMap::<_, usize>::default,
-            |mut frequencies, _| {
-                let mut rng = thread_rng();
-                let start = rng.gen_range(0..graph.node_count());
-                let end = rng.gen_range(0..graph.node_count());
-                let (_, path) =
-                    petgraph::algo::astar(&graph, start.into(), |finish| finish == end.into(), |_| 1, |_| 0).unwrap();
-                path.windows(2)
-                    .map(|window| graph.edges_connecting(window[0], window[1]).next().unwrap())
-                    .for_each(|edge| {
-                        *frequencies.entry(edge.id()).or_default() += 1;
-                    });
-                frequencies
-            },
-        )
-        .reduce(
-            HashMap::<_, usize>::default,
-            |mut frequencies, other_frequencies| {
-                for (edge, count) in other_frequencies {
-                    *frequencies.entry(edge).or_default() += count;
-                }
-                frequencies
-            },
-        );
+        .fold(HashMap::<_, usize>::default, sample_path)
+        .reduce(HashMap::<_, usize>::default, |mut frequencies, other_frequencies| {
+            for (edge, count) in other_frequencies {
+                *frequencies.entry(edge).or_default() += count;
+            }
+            frequencies
+        });
+    #[cfg(not(feature = "parallel"))]
+    let frequencies = (0..SAMPLE_POINTS).fold(HashMap::<_, usize>::default(), sample_path);
 
     let mut frequencies = frequencies.into_iter().collect::<Vec<_>>();
     frequencies.sort_by_key(|(_, count)| *count);
@@ -76,10 +138,26 @@ mod tests {
     #[test]
     fn works_90_percent_of_the_time() {
         let correct = (0..TRIALS)
-            .map(|_| do_solve(load_input()))
+            .map(|_| do_solve(load_input(Puzzle::INPUT).unwrap()))
             .filter(|answer| *answer == RIGHT_ANSWER)
             .count();
         eprintln!("{:.2}% correct", correct * 100 / TRIALS);
         assert!(correct > TRIALS * 9 / 10);
     }
+
+    #[test]
+    fn example() {
+        // Like with the real input, sampling paths at random only gets it right most of the time.
+        let graph = load_input(include_str!("sample_input.txt")).unwrap();
+        assert!((0..5).any(|_| do_solve(graph.clone()) == 54));
+    }
+
+    #[test]
+    fn disconnected_wiring_breaks_assumptions() {
+        let graph = Puzzle::parse(include_str!("sample_input.txt")).unwrap();
+        assert_eq!(Puzzle::check_assumptions(&graph), Ok(()));
+
+        let graph = Puzzle::parse("abc: def\nghi: jkl").unwrap();
+        assert!(Puzzle::check_assumptions(&graph).is_err());
+    }
 }
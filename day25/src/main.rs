@@ -1,5 +1,8 @@
-fn main() {
-    let (part1, part2) = day25::solve();
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+    let (part1, part2) = day25::solve()?;
+    aoc_common::bounds::warn_if_ruled_out("day25", &part1, &part2);
     println!("{part1}");
     println!("{part2}");
+    Ok(())
 }
@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_common::Solution as _;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    // Malformed input should be reported as an error, not panic.
+    let _ = day05::Puzzle::parse(input);
+});
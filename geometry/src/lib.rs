@@ -0,0 +1,186 @@
+use aoc_math::gcd_u128;
+
+/// A point on the plane, as `(x, y)`. Coordinates are `i128`s so that areas of big polygons can't overflow.
+pub type Point = (i128, i128);
+
+/// Which way a polygon goes round, taking `y` to point up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// A closed polygon, with an edge from each vertex to the next and from the last one back to the first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Self {
+        Self { vertices }
+    }
+
+    /// Start at the origin, and take each of `steps`, as `(dx, dy)`, in turn. Ending up back at the origin is optional,
+    /// as the last vertex joins back up with the first either way.
+    pub fn from_steps(steps: impl IntoIterator<Item = Point>) -> Self {
+        let mut vertices = vec![(0, 0)];
+        let mut pos = (0, 0);
+        for (dx, dy) in steps {
+            pos = (pos.0 + dx, pos.1 + dy);
+            vertices.push(pos);
+        }
+        if vertices.len() > 1 && pos == (0, 0) {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Every edge, as its two ends, in order.
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Twice the area, which keeps it a whole number, positive when the polygon goes counter-clockwise. Using the
+    /// shoelace formula, this only makes sense if the polygon [is simple](Polygon::is_simple).
+    pub fn double_signed_area(&self) -> i128 {
+        self.edges().map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1).sum()
+    }
+
+    /// Which way the polygon goes round, or `None` if it doesn't enclose anything.
+    pub fn orientation(&self) -> Option<Orientation> {
+        match self.double_signed_area().signum() {
+            1 => Some(Orientation::CounterClockwise),
+            -1 => Some(Orientation::Clockwise),
+            _ => None,
+        }
+    }
+
+    /// How many points with whole coordinates are on the edges.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| gcd_u128((x2 - x1).unsigned_abs(), (y2 - y1).unsigned_abs()) as i128)
+            .sum()
+    }
+
+    /// How many points with whole coordinates are inside the polygon, not counting the edges. For a simple polygon
+    /// whose vertices all have whole coordinates, Pick's theorem says that
+    /// `area = interior points + boundary points / 2 - 1`, which we can turn around.
+    pub fn interior_points(&self) -> i128 {
+        (self.double_signed_area().abs() - self.boundary_points() + 2) / 2
+    }
+
+    /// Whether the edges never cross, touch, or double back on each other, other than each edge meeting the next at
+    /// the vertex between them. Checks every pair of edges, so it's quadratic in the number of vertices.
+    pub fn is_simple(&self) -> bool {
+        let edges = self.edges().collect::<Vec<_>>();
+        if edges.len() < 3 {
+            return false;
+        }
+        for (i, &(a, b)) in edges.iter().enumerate() {
+            let (_, c) = edges[(i + 1) % edges.len()];
+            // Going straight back the way the edge came overlaps it.
+            if a == b || (cross(a, b, c) == 0 && dot((b.0 - a.0, b.1 - a.1), (c.0 - b.0, c.1 - b.1)) < 0) {
+                return false;
+            }
+            // Skip the edges on either side, which always meet this one.
+            let others = (i + 2..edges.len()).take(edges.len() - 3);
+            if others.map(|j| edges[j]).any(|other| segments_meet((a, b), other)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Positive if going from `o` to `a` to `b` turns left, negative if it turns right, and zero if it goes straight.
+fn cross(o: Point, a: Point, b: Point) -> i128 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+fn dot(a: Point, b: Point) -> i128 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Whether `p`, which is in line with the segment from `a` to `b`, is on it.
+fn within(p: Point, (a, b): (Point, Point)) -> bool {
+    (a.0.min(b.0)..=a.0.max(b.0)).contains(&p.0) && (a.1.min(b.1)..=a.1.max(b.1)).contains(&p.1)
+}
+
+/// Whether two segments have any point in common.
+fn segments_meet((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let (ab_c, ab_d) = (cross(a, b, c).signum(), cross(a, b, d).signum());
+    let (cd_a, cd_b) = (cross(c, d, a).signum(), cross(c, d, b).signum());
+    (ab_c * ab_d < 0 && cd_a * cd_b < 0)
+        || (ab_c == 0 && within(c, (a, b)))
+        || (ab_d == 0 && within(d, (a, b)))
+        || (cd_a == 0 && within(a, (c, d)))
+        || (cd_b == 0 && within(b, (c, d)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(side: i128) -> Polygon {
+        Polygon::from_steps([(side, 0), (0, side), (-side, 0), (0, -side)])
+    }
+
+    #[test]
+    fn squares() {
+        let polygon = square(3);
+        assert_eq!(polygon.vertices(), [(0, 0), (3, 0), (3, 3), (0, 3)]);
+        assert_eq!(polygon.double_signed_area(), 18);
+        assert_eq!(polygon.orientation(), Some(Orientation::CounterClockwise));
+        assert_eq!(polygon.boundary_points(), 12);
+        assert_eq!(polygon.interior_points(), 4);
+        assert!(polygon.is_simple());
+
+        let reversed = Polygon::new(polygon.vertices().iter().rev().copied().collect());
+        assert_eq!(reversed.double_signed_area(), -18);
+        assert_eq!(reversed.orientation(), Some(Orientation::Clockwise));
+        assert_eq!(reversed.interior_points(), 4);
+
+        // Not coming back to the origin still closes the polygon.
+        let open = Polygon::from_steps([(3, 0), (0, 3), (-3, 0)]);
+        assert_eq!(open, polygon);
+    }
+
+    #[test]
+    fn pick_counts_lattice_points() {
+        // A right triangle with legs of 4, whose hypotenuse only passes through whole points every 2 along.
+        let triangle = Polygon::new(vec![(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.interior_points(), 3);
+
+        let slanted = Polygon::new(vec![(0, 0), (3, 1), (1, 3)]);
+        assert_eq!(slanted.double_signed_area(), 8);
+        assert_eq!(slanted.boundary_points(), 4);
+        assert_eq!(slanted.interior_points(), 3);
+
+        // Big enough that an `i64` area would overflow.
+        let huge = square(1 << 40);
+        assert_eq!(huge.interior_points(), ((1 << 40) - 1) * ((1 << 40) - 1));
+    }
+
+    #[test]
+    fn spots_self_intersections() {
+        let bowtie = Polygon::new(vec![(0, 0), (2, 2), (2, 0), (0, 2)]);
+        assert!(!bowtie.is_simple());
+
+        // Two squares meeting at a corner.
+        let touching = Polygon::from_steps([(1, 0), (0, 1), (1, 0), (0, 1), (-1, 0), (0, -1), (-1, 0), (0, -1)]);
+        assert!(!touching.is_simple());
+
+        let doubling_back = Polygon::from_steps([(2, 0), (-1, 0), (0, 1)]);
+        assert!(!doubling_back.is_simple());
+
+        assert!(!Polygon::from_steps([(1, 0)]).is_simple());
+        assert_eq!(Polygon::default().orientation(), None);
+    }
+}
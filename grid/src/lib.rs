@@ -0,0 +1,314 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use aoc_common::Unexpected;
+
+/// A position on a grid, as `(row, column)` counting from the top left.
+pub type Pos = (usize, usize);
+
+/// The four ways to go on a grid, in clockwise order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    /// How going this way changes a position, as `(rows, columns)`.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+
+    pub const fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    pub const fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub const fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+}
+
+/// The offsets, as `(rows, columns)`, of the eight positions around one.
+const AROUND: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A rectangle of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Lay `cells` out in rows `width` long.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is zero while there are cells, or the cells don't fill up the last row.
+    pub fn from_vec(cells: Vec<T>, width: usize) -> Self {
+        let height = if cells.is_empty() { 0 } else { cells.len() / width };
+        assert_eq!(height * width, cells.len(), "cells should fill rows {width} long");
+        Self { cells, width, height }
+    }
+
+    /// Parse a map with one byte per cell, turning each into a cell with `cell`. Every row has to be as long as the
+    /// first one, and there has to be at least one. When `cell` rejects a byte, the reason it gives is reported at it.
+    pub fn parse<E>(input: &str, mut cell: impl FnMut(Pos, u8) -> Result<T, E>) -> Result<Self, Unexpected<'_>>
+    where
+        E: Into<String>,
+    {
+        let width = input.lines().next().map_or(0, str::len);
+        if width == 0 {
+            return Err(Unexpected::new(input, "expected a map"));
+        }
+        let mut cells = Vec::with_capacity(input.len());
+        let mut height = 0;
+        for (y, row) in input.lines().enumerate() {
+            if row.len() != width {
+                return Err(Unexpected::new(row, format!("expected a row {width} tiles long")));
+            }
+            for (x, b) in row.bytes().enumerate() {
+                match cell((y, x), b) {
+                    Ok(cell) => cells.push(cell),
+                    Err(reason) => {
+                        let start = (0..=x).rev().find(|&idx| row.is_char_boundary(idx)).unwrap_or(0);
+                        return Err(Unexpected::new(&row[start..], reason));
+                    }
+                }
+            }
+            height += 1;
+        }
+        Ok(Self { cells, width, height })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, (y, x): Pos) -> bool {
+        y < self.height && x < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.in_bounds(pos).then(|| &self.cells[self.index_of(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.in_bounds(pos).then(|| {
+            let idx = self.index_of(pos);
+            &mut self.cells[idx]
+        })
+    }
+
+    /// Where the cell at `pos` is in [`Grid::cells`].
+    pub fn index_of(&self, (y, x): Pos) -> usize {
+        y * self.width + x
+    }
+
+    /// Where the cell at `index` in [`Grid::cells`] is on the grid.
+    pub fn pos_of(&self, index: usize) -> Pos {
+        (index / self.width, index % self.width)
+    }
+
+    /// The position `offset`, as `(rows, columns)`, away from `pos`, if that's still on the grid.
+    pub fn offset(&self, (y, x): Pos, (dy, dx): (isize, isize)) -> Option<Pos> {
+        let pos = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+        self.in_bounds(pos).then_some(pos)
+    }
+
+    /// The position next to `pos` going `dir`, if that's still on the grid.
+    pub fn step(&self, pos: Pos, dir: Direction) -> Option<Pos> {
+        self.offset(pos, dir.offset())
+    }
+
+    /// Like [`Grid::step`], but for indices into [`Grid::cells`].
+    pub fn step_index(&self, index: usize, dir: Direction) -> Option<usize> {
+        Some(match dir {
+            Direction::Up => index.checked_sub(self.width)?,
+            Direction::Right if !(index + 1).is_multiple_of(self.width) => index + 1,
+            Direction::Down if index + self.width < self.cells.len() => index + self.width,
+            Direction::Left if !index.is_multiple_of(self.width) => index - 1,
+            _ => return None,
+        })
+    }
+
+    /// The positions next to `pos` on the grid, up to four of them.
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction::ALL.into_iter().filter_map(move |dir| self.step(pos, dir))
+    }
+
+    /// The positions around `pos` on the grid, diagonals included, up to eight of them.
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        AROUND.into_iter().filter_map(move |offset| self.offset(pos, offset))
+    }
+
+    /// Every position on the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (y, x)))
+    }
+
+    /// Where the first cell, row by row, that `predicate` accepts is.
+    pub fn find(&self, predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.cells.iter().position(predicate).map(|idx| self.pos_of(idx))
+    }
+
+    /// All the cells, row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..][..self.width]
+    }
+
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> {
+        // `chunks_exact` doesn't like a width of zero, even with nothing to chunk.
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl ExactSizeIterator<Item = &T> {
+        self.rows().map(move |row| &row[x])
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        assert!(self.in_bounds(pos), "{pos:?} is outside of the grid");
+        &self.cells[self.index_of(pos)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        assert!(self.in_bounds(pos), "{pos:?} is outside of the grid");
+        let idx = self.index_of(pos);
+        &mut self.cells[idx]
+    }
+}
+
+/// Draw the grid row by row, one line each.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Grid<char> {
+        Grid::parse("abc\ndef", |_, b| Ok::<_, String>(char::from(b))).unwrap()
+    }
+
+    #[test]
+    fn parses_and_renders() {
+        let grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], 'd');
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    fn reports_bad_maps() {
+        let input = "ab\nc";
+        let err = Grid::parse(input, |_, b| Ok::<_, String>(b)).unwrap_err().locate(input);
+        assert_eq!(
+            (err.line, err.column, err.reason.as_str()),
+            (2, 1, "expected a row 2 tiles long")
+        );
+
+        let input = "abc\nxé";
+        let err = Grid::parse(input, |_, b| if b.is_ascii() { Ok(b) } else { Err("not ASCII") })
+            .unwrap_err()
+            .locate(input);
+        assert_eq!((err.line, err.column), (2, 2));
+
+        assert!(Grid::parse("", |_, b| Ok::<_, String>(b)).is_err());
+    }
+
+    #[test]
+    fn neighbors_stay_in_bounds() {
+        let grid = example();
+        assert_eq!(grid.neighbors4((0, 0)).collect::<Vec<_>>(), [(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors4((1, 1)).collect::<Vec<_>>(), [(0, 1), (1, 2), (1, 0)]);
+        assert_eq!(grid.neighbors8((0, 2)).collect::<Vec<_>>(), [(0, 1), (1, 1), (1, 2)]);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn steps_by_index_like_by_position() {
+        let grid = example();
+        for pos in grid.positions() {
+            for dir in Direction::ALL {
+                let by_index = grid.step_index(grid.index_of(pos), dir);
+                assert_eq!(by_index, grid.step(pos, dir).map(|pos| grid.index_of(pos)));
+            }
+        }
+    }
+
+    #[test]
+    fn turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+            let ((dy, dx), (oy, ox)) = (dir.offset(), dir.opposite().offset());
+            assert_eq!((dy + oy, dx + ox), (0, 0));
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    }
+}
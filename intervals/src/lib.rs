@@ -0,0 +1,308 @@
+use std::fmt::Display;
+
+/// The numbers from `start` up to, but not including, `end`. It's empty if `end` isn't past `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Interval {
+    pub const fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// The `len` numbers counting up from `start`.
+    pub const fn from_len(start: u64, len: u64) -> Self {
+        Self::new(start, start + len)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.end <= self.start
+    }
+
+    pub const fn len(self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub const fn contains(self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// `self`, unless it's empty.
+    pub fn non_empty(self) -> Option<Self> {
+        (!self.is_empty()).then_some(self)
+    }
+
+    /// The numbers in both `self` and `other`, if there are any.
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end)).non_empty()
+    }
+
+    /// Split `self` into the numbers below `at` and the numbers from `at` onwards, either of which might be empty.
+    pub fn split_at(self, at: u64) -> (Option<Self>, Option<Self>) {
+        (
+            Self::new(self.start, self.end.min(at)).non_empty(),
+            Self::new(self.start.max(at), self.end).non_empty(),
+        )
+    }
+
+    /// Every number in `self` moved by `by`, or `None` if that would go out of bounds.
+    pub fn shift(self, by: i64) -> Option<Self> {
+        Some(Self::new(
+            self.start.checked_add_signed(by)?,
+            self.end.checked_add_signed(by)?,
+        ))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Some numbers, kept as the fewest intervals that cover them: sorted, without any empty ones, and with a gap between
+/// each.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the numbers in `interval`, merging it with every interval it overlaps or touches.
+    pub fn insert(&mut self, mut interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|other| other.end < interval.start);
+        let last = self.intervals.partition_point(|other| other.start <= interval.end);
+        if first < last {
+            interval.start = interval.start.min(self.intervals[first].start);
+            interval.end = interval.end.max(self.intervals[last - 1].end);
+        }
+        self.intervals.splice(first..last, [interval]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        other.iter().for_each(|interval| union.insert(interval));
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut intervals = Vec::new();
+        while let (Some(&a), Some(&b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            intervals.extend(a.intersection(b));
+            // Whichever ends first can't overlap anything else in the other set.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals }
+    }
+
+    /// The numbers in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let mut first = 0;
+        for &interval in &self.intervals {
+            // Skip what's entirely below this interval, which is also below every one after it.
+            while other
+                .intervals
+                .get(first)
+                .is_some_and(|other| other.end <= interval.start)
+            {
+                first += 1;
+            }
+            let mut rest = interval;
+            for &hole in other.intervals[first..]
+                .iter()
+                .take_while(|hole| hole.start < interval.end)
+            {
+                let (before, _) = rest.split_at(hole.start);
+                intervals.extend(before);
+                rest.start = hole.end.clamp(rest.start, rest.end);
+            }
+            intervals.extend(rest.non_empty());
+        }
+        Self { intervals }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many numbers there are.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.intervals.first().map(|interval| interval.start)
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        let idx = self.intervals.partition_point(|interval| interval.end <= value);
+        self.intervals.get(idx).is_some_and(|interval| interval.contains(value))
+    }
+
+    /// The intervals, in order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Interval> + '_ {
+        self.intervals.iter().copied()
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        Self {
+            intervals: interval.non_empty().into_iter().collect(),
+        }
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|interval| set.insert(interval));
+        set
+    }
+}
+
+impl Extend<Interval> for IntervalSet {
+    fn extend<I: IntoIterator<Item = Interval>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|interval| self.insert(interval));
+    }
+}
+
+/// Every point whose `N` coordinates each fall in the matching interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub axes: [Interval; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|axis| axis.is_empty())
+    }
+
+    /// How many points there are.
+    pub fn volume(&self) -> u64 {
+        self.axes.iter().map(|axis| axis.len()).product()
+    }
+
+    /// The points in both `self` and `other`, if there are any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.axes;
+        for (axis, other) in axes.iter_mut().zip(other.axes) {
+            *axis = axis.intersection(other)?;
+        }
+        Some(Self { axes })
+    }
+
+    /// Split `self` along `axis` like [`Interval::split_at`].
+    pub fn split_at(&self, axis: usize, at: u64) -> (Option<Self>, Option<Self>) {
+        let (below, above) = self.axes[axis].split_at(at);
+        let with = |interval| {
+            let mut axes = self.axes;
+            axes[axis] = interval;
+            Self { axes }
+        };
+        (below.map(with), above.map(with))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(u64, u64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(start, end)| Interval::new(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn intervals_are_half_open() {
+        let interval = Interval::new(3, 6);
+        assert_eq!(interval.len(), 3);
+        assert!(interval.contains(3) && !interval.contains(6));
+        assert_eq!(Interval::from_len(3, 3), interval);
+        assert!(Interval::new(4, 4).is_empty() && Interval::new(5, 4).is_empty());
+        assert_eq!(Interval::new(5, 4).len(), 0);
+
+        // Intervals that only touch have nothing in common.
+        assert_eq!(interval.intersection(Interval::new(6, 9)), None);
+        assert_eq!(interval.intersection(Interval::new(5, 9)), Some(Interval::new(5, 6)));
+    }
+
+    #[test]
+    fn splits_at_the_edges() {
+        let interval = Interval::new(3, 6);
+        assert_eq!(interval.split_at(3), (None, Some(interval)));
+        assert_eq!(interval.split_at(6), (Some(interval), None));
+        assert_eq!(
+            interval.split_at(4),
+            (Some(Interval::new(3, 4)), Some(Interval::new(4, 6)))
+        );
+        assert_eq!(interval.split_at(0), (None, Some(interval)));
+        assert_eq!(interval.shift(-3), Some(Interval::new(0, 3)));
+        assert_eq!(interval.shift(-4), None);
+    }
+
+    #[test]
+    fn sets_coalesce() {
+        let numbers = set(&[(5, 7), (1, 3), (3, 4), (9, 9), (10, 12)]);
+        assert_eq!(
+            numbers.iter().collect::<Vec<_>>(),
+            [(1, 4), (5, 7), (10, 12)].map(|(s, e)| Interval::new(s, e))
+        );
+        assert_eq!(numbers.len(), 7);
+        assert_eq!(numbers.min(), Some(1));
+        assert!(numbers.contains(3) && !numbers.contains(4) && numbers.contains(11) && !numbers.contains(12));
+
+        let mut bridged = numbers.clone();
+        bridged.insert(Interval::new(4, 10));
+        assert_eq!(bridged, set(&[(1, 12)]));
+        assert!(IntervalSet::from(Interval::new(2, 2)).is_empty());
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a = set(&[(0, 5), (10, 15)]);
+        let b = set(&[(3, 12), (14, 20)]);
+        assert_eq!(a.union(&b), set(&[(0, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 12), (14, 15)]));
+        assert_eq!(a.difference(&b), set(&[(0, 3), (12, 14)]));
+        assert_eq!(b.difference(&a), set(&[(5, 10), (15, 20)]));
+
+        // Holes right at the edges, or covering everything, leave nothing behind there.
+        assert_eq!(set(&[(0, 10)]).difference(&set(&[(0, 2), (8, 10)])), set(&[(2, 8)]));
+        assert!(a.difference(&set(&[(0, 20)])).is_empty());
+        assert_eq!(a.difference(&set(&[(5, 10)])), a);
+    }
+
+    #[test]
+    fn cuboids() {
+        let cuboid = Cuboid {
+            axes: [Interval::new(1, 4001); 4],
+        };
+        assert_eq!(cuboid.volume(), 4000u64.pow(4));
+
+        let (below, above) = cuboid.split_at(2, 1351);
+        let (below, above) = (below.unwrap(), above.unwrap());
+        assert_eq!(below.axes[2], Interval::new(1, 1351));
+        assert_eq!(below.volume() + above.volume(), cuboid.volume());
+        assert_eq!(below.intersection(&above), None);
+        assert_eq!(cuboid.intersection(&below), Some(below));
+        assert!(!below.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+/// The greatest common divisor of `a` and `b`, where `gcd(a, 0) == a`.
+pub const fn gcd(a: u64, b: u64) -> u64 {
+    gcd_u128(a as u128, b as u128) as u64
+}
+
+/// [`gcd`] for `u128`s.
+pub const fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The least common multiple of `a` and `b`, or `None` if it doesn't fit in a `u64`.
+pub const fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// The least common multiple of `a` and `b`.
+///
+/// # Panics
+///
+/// If it doesn't fit in a `u64`.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    checked_lcm(a, b).unwrap_or_else(|| panic!("the lcm of {a} and {b} overflows"))
+}
+
+/// The `x` modulo `modulus` for which `a * x` is 1 modulo `modulus`, if `a` and `modulus` are coprime.
+fn inverse(a: u64, modulus: u64) -> Option<u64> {
+    let (mut old_r, mut r) = (i128::from(a), i128::from(modulus));
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(i128::from(modulus)) as u64)
+}
+
+/// Solve `x ≡ a (mod m)` and `x ≡ b (mod n)` together, even if `m` and `n` have factors in common. Returns the
+/// smallest such `x` along with the modulus every other one is congruent to it under (the lcm of `m` and `n`), or
+/// `None` if there's no such `x` or that lcm doesn't fit in a `u64`.
+///
+/// # Panics
+///
+/// If either modulus is zero.
+pub fn crt((a, m): (u64, u64), (b, n): (u64, u64)) -> Option<(u64, u64)> {
+    assert!(m != 0 && n != 0, "moduli should be non-zero");
+    let (a, b) = (a % m, b % n);
+    let g = gcd(m, n);
+    let difference = i128::from(b) - i128::from(a);
+    if difference % i128::from(g) != 0 {
+        return None;
+    }
+    let modulus = checked_lcm(m, n)?;
+
+    // x = a + k * m, where k * m ≡ b - a (mod n), so k ≡ (b - a) / g * (m / g)⁻¹ (mod n / g).
+    let n_g = n / g;
+    let k = (difference / i128::from(g)).rem_euclid(i128::from(n_g)) as u128;
+    let k = k * u128::from(inverse((m / g) % n_g, n_g)?) % u128::from(n_g);
+    // Both terms are below `modulus`, so this can't overflow.
+    let x = (u128::from(a) + k * u128::from(m)) % u128::from(modulus);
+    Some((x as u64, modulus))
+}
+
+/// Something that happens periodically: first at `start`, then every `period` after that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+}
+
+impl Cycle {
+    /// When both `self` and `other` happen at once, or `None` if they never do (or not before overflowing a `u64`).
+    pub fn meet(self, other: Self) -> Option<Self> {
+        let (x, period) = crt((self.start, self.period), (other.start, other.period))?;
+        // Neither has started before its start, so catch up to the later one.
+        let earliest = self.start.max(other.start);
+        let start = if x >= earliest {
+            x
+        } else {
+            (earliest - x).div_ceil(period).checked_mul(period)?.checked_add(x)?
+        };
+        Some(Self { start, period })
+    }
+
+    /// When all of `cycles` happen at once, like [`Cycle::meet`].
+    pub fn align(cycles: impl IntoIterator<Item = Self>) -> Option<Self> {
+        let mut cycles = cycles.into_iter();
+        let first = cycles.next()?;
+        cycles.try_fold(first, Self::meet)
+    }
+
+    /// The earliest step that has to be just like step `n`, taking `self` as when a sequence starts going round in
+    /// circles and how long those are.
+    pub fn earliest(self, n: u64) -> u64 {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+/// Find where the sequence that starts with `first` and carries on with `next` goes round in circles, using Brent's
+/// algorithm: the number of steps before the loop starts, and how long it is. Loops forever if the sequence doesn't.
+pub fn brent<T: Clone + PartialEq>(first: T, mut next: impl FnMut(&T) -> T) -> Cycle {
+    // Find the period, by having the tortoise wait at every power of two for the hare to come back round.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = first.clone();
+    let mut hare = next(&first);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    // Then with the hare a period ahead, they meet right where the loop starts.
+    let mut tortoise = first.clone();
+    let mut hare = first;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Like [`brent`], but using Floyd's algorithm, which usually takes more steps to get there.
+pub fn floyd<T: Clone + PartialEq>(first: T, mut next: impl FnMut(&T) -> T) -> Cycle {
+    // The hare goes twice as fast, so they meet somewhere in the loop, a multiple of the period in.
+    let mut tortoise = next(&first);
+    let mut hare = next(&tortoise);
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        let halfway = next(&hare);
+        hare = next(&halfway);
+    }
+
+    // That puts the hare as far from the loop's start as the first state is.
+    let mut start = 0;
+    tortoise = first;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    while tortoise != hare {
+        hare = next(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(gcd_u128(1 << 100, 3 << 90), 1 << 90);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(checked_lcm(0, 5), Some(0));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+    }
+
+    #[test]
+    fn crt_with_common_factors() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((3, 4), (1, 6)), Some((7, 12)));
+        assert_eq!(crt((0, 4), (1, 6)), None);
+        let (m, n) = (1 << 31, (1 << 31) + 1);
+        let (x, modulus) = crt((5, m), (7, n)).unwrap();
+        assert_eq!((x % m, x % n, modulus), (5, 7, m * n));
+        assert_eq!(crt((0, 1 << 40), (0, (1 << 40) + 1)), None);
+    }
+
+    #[test]
+    fn cycles_meet_after_both_start() {
+        let a = Cycle { start: 10, period: 4 };
+        let b = Cycle { start: 3, period: 6 };
+        assert_eq!(a.meet(b), None);
+        let b = Cycle { start: 4, period: 6 };
+        assert_eq!(a.meet(b), Some(Cycle { start: 10, period: 12 }));
+        let periods = [3, 5, 4].map(|period| Cycle { start: period, period });
+        assert_eq!(Cycle::align(periods), Some(Cycle { start: 60, period: 60 }));
+    }
+
+    #[test]
+    fn finds_cycles() {
+        // 0, 1, 2, 3, 4, 5, 2, 3, ...
+        let next = |&x: &u32| if x == 5 { 2 } else { x + 1 };
+        let cycle = Cycle { start: 2, period: 4 };
+        assert_eq!(brent(0, next), cycle);
+        assert_eq!(floyd(0, next), cycle);
+        assert_eq!(cycle.earliest(1), 1);
+        assert_eq!(cycle.earliest(9), 5);
+        assert_eq!(brent(7, |&x| x), Cycle { start: 0, period: 1 });
+    }
+}
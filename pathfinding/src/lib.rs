@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+
+use ::bucket_queue::{BucketQueue, LastInFirstOutQueue as _, Queue as _};
+
+/// Something to find paths through: nodes that can be numbered from zero, and the edges leading out of each of them.
+pub trait Graph {
+    type Node: Copy;
+
+    /// How many nodes there are, so that every [`Graph::index`] is below this.
+    fn node_count(&self) -> usize;
+
+    /// Number `node`, so searches can keep track of it in a flat list rather than a map. Searches skip any starts
+    /// numbered [`Graph::node_count`] or above.
+    ///
+    /// # Panics
+    ///
+    /// Searches panic if a node reached from a start has a number that high.
+    fn index(&self, node: Self::Node) -> usize;
+
+    /// Call `edge` with every node one edge away from `node`, along with what it costs to take that edge.
+    fn successors(&self, node: Self::Node, edge: impl FnMut(Self::Node, usize));
+}
+
+/// What a search found out: how far away the nodes it reached are, and how it got to them.
+pub struct Search<'g, G: Graph> {
+    graph: &'g G,
+    distances: Vec<usize>,
+    came_from: Vec<Option<G::Node>>,
+    settled: Vec<G::Node>,
+    goal: Option<G::Node>,
+}
+
+impl<'g, G: Graph> Search<'g, G> {
+    fn new(graph: &'g G) -> Self {
+        Self {
+            graph,
+            distances: vec![usize::MAX; graph.node_count()],
+            came_from: vec![None; graph.node_count()],
+            settled: Vec::new(),
+            goal: None,
+        }
+    }
+
+    /// Start searching from `node`, unless that's already being done or it isn't in the graph.
+    fn start(&mut self, node: G::Node) -> bool {
+        let Some(distance) = self.distances.get_mut(self.graph.index(node)) else {
+            return false;
+        };
+        let fresh = *distance != 0;
+        *distance = 0;
+        fresh
+    }
+
+    /// The goal the search stopped at, and how far away it is.
+    pub fn goal(&self) -> Option<(G::Node, usize)> {
+        self.goal.map(|goal| (goal, self.distances[self.graph.index(goal)]))
+    }
+
+    /// How far away `node` is, if the search got to it. It might not be the shortest distance yet if the search stopped
+    /// at its goal before `node` was settled.
+    pub fn distance(&self, node: G::Node) -> Option<usize> {
+        let distance = self.distances.get(self.graph.index(node))?;
+        Some(*distance).filter(|&distance| distance != usize::MAX)
+    }
+
+    /// The way from one of the starts to `node`, both included, if the search got to it.
+    pub fn path_to(&self, node: G::Node) -> Option<Vec<G::Node>> {
+        self.distance(node)?;
+        let mut path = vec![node];
+        while let Some(previous) = self.came_from[self.graph.index(*path.last().unwrap())] {
+            path.push(previous);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every node whose distance is known for sure along with that distance, in the order they were settled: closest
+    /// first, unless the search was guided by a heuristic.
+    pub fn settled(&self) -> impl ExactSizeIterator<Item = (G::Node, usize)> + '_ {
+        self.settled
+            .iter()
+            .map(|&node| (node, self.distances[self.graph.index(node)]))
+    }
+
+    /// Where the search keeps track of `node`, which it reached by following an edge.
+    fn index_of_next(&self, node: G::Node) -> usize {
+        let idx = self.graph.index(node);
+        let count = self.distances.len();
+        assert!(
+            idx < count,
+            "an edge leads to node number {idx}, but the graph only has {count} nodes"
+        );
+        idx
+    }
+}
+
+/// Find the cheapest ways from `starts` to every other node, stopping early once one that `is_goal` is settled. Costs
+/// are kept in a bucket queue, so they should stay reasonably small.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    is_goal: impl FnMut(G::Node) -> bool,
+) -> Search<'_, G> {
+    astar(graph, starts, is_goal, |_| 0)
+}
+
+/// Like [`dijkstra`], but heading for the goal first: `heuristic` guesses how much is left to pay from a node to the
+/// nearest goal. The guess must never overshoot, nor drop by more than an edge costs along it, or the cheapest way
+/// might be missed. Nodes get settled in order of how cheap a way through them looks rather than how close they are.
+pub fn astar<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+    mut heuristic: impl FnMut(G::Node) -> usize,
+) -> Search<'_, G> {
+    let mut search = Search::new(graph);
+    let mut queue = BucketQueue::<Vec<_>>::new();
+    for start in starts {
+        if search.start(start) {
+            queue.push((start, 0), heuristic(start));
+        }
+    }
+
+    while let Some(estimate) = queue.min_priority() {
+        let (node, distance) = queue.pop(estimate).unwrap();
+        // Nodes get queued again whenever a cheaper way to them turns up, so skip the ways that didn't pan out.
+        if distance > search.distances[graph.index(node)] {
+            continue;
+        }
+        search.settled.push(node);
+        if is_goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        graph.successors(node, |next, cost| {
+            let next_distance = distance + cost;
+            let next_idx = search.index_of_next(next);
+            if next_distance < search.distances[next_idx] {
+                search.distances[next_idx] = next_distance;
+                search.came_from[next_idx] = Some(node);
+                queue.push((next, next_distance), next_distance + heuristic(next));
+            }
+        });
+    }
+
+    search
+}
+
+/// Find the fewest edges it takes to get from `starts` to every other node, ignoring what the edges cost, stopping
+/// early once one that `is_goal` is reached.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+) -> Search<'_, G> {
+    let mut search = Search::new(graph);
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if search.start(start) {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let distance = search.distances[graph.index(node)];
+        search.settled.push(node);
+        if is_goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        graph.successors(node, |next, _| {
+            let next_idx = search.index_of_next(next);
+            if search.distances[next_idx] == usize::MAX {
+                search.distances[next_idx] = distance + 1;
+                search.came_from[next_idx] = Some(node);
+                queue.push_back(next);
+            }
+        });
+    }
+
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edges out of each node, as `(to, cost)`.
+    struct Edges(Vec<Vec<(usize, usize)>>);
+
+    impl Graph for Edges {
+        type Node = usize;
+
+        fn node_count(&self) -> usize {
+            self.0.len()
+        }
+
+        fn index(&self, node: usize) -> usize {
+            node
+        }
+
+        fn successors(&self, node: usize, mut edge: impl FnMut(usize, usize)) {
+            self.0[node].iter().for_each(|&(to, cost)| edge(to, cost));
+        }
+    }
+
+    /// A shortcut from 0 to 3 that costs more than going the long way round, and a node 4 that can't be reached.
+    fn example() -> Edges {
+        Edges(vec![
+            vec![(1, 1), (3, 5)],
+            vec![(2, 1)],
+            vec![(3, 1)],
+            vec![(0, 1)],
+            vec![(0, 1)],
+        ])
+    }
+
+    #[test]
+    fn dijkstra_takes_the_cheapest_way() {
+        let graph = example();
+        let search = dijkstra(&graph, [0], |_| false);
+        assert_eq!(search.distance(3), Some(3));
+        assert_eq!(search.path_to(3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(search.distance(4), None);
+        assert_eq!(search.path_to(4), None);
+        assert_eq!(search.settled().collect::<Vec<_>>(), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn bfs_takes_the_fewest_edges() {
+        let graph = example();
+        let search = bfs(&graph, [0], |_| false);
+        assert_eq!(search.distance(3), Some(1));
+        assert_eq!(search.path_to(3), Some(vec![0, 3]));
+        assert_eq!(search.distance(2), Some(2));
+        assert_eq!(search.distance(4), None);
+    }
+
+    #[test]
+    fn stops_at_the_goal() {
+        let graph = example();
+        let search = dijkstra(&graph, [4, 1], |node| node == 0);
+        assert_eq!(search.goal(), Some((0, 1)));
+        assert_eq!(search.path_to(0), Some(vec![4, 0]));
+        assert_eq!(bfs(&graph, [1], |node| node == 0).goal(), Some((0, 3)));
+    }
+
+    #[test]
+    fn skips_starts_outside_the_graph() {
+        let graph = example();
+        let search = bfs(&graph, [5, 4], |_| false);
+        assert_eq!(search.distance(4), Some(0));
+        assert_eq!(search.distance(5), None);
+        assert!(dijkstra(&Edges(Vec::new()), [0], |_| true).goal().is_none());
+    }
+
+    #[test]
+    #[should_panic = "an edge leads to node number 5, but the graph only has 2 nodes"]
+    fn panics_on_edges_leaving_the_graph() {
+        let graph = Edges(vec![vec![(1, 1)], vec![(5, 1)]]);
+        dijkstra(&graph, [0], |_| false);
+    }
+
+    /// A `width` by `height` grid, where stepping onto a cell costs whatever is written in it.
+    struct Costs {
+        width: usize,
+        cells: Vec<usize>,
+    }
+
+    impl Graph for Costs {
+        type Node = (usize, usize);
+
+        fn node_count(&self) -> usize {
+            self.cells.len()
+        }
+
+        fn index(&self, (x, y): (usize, usize)) -> usize {
+            y * self.width + x
+        }
+
+        fn successors(&self, (x, y): (usize, usize), mut edge: impl FnMut((usize, usize), usize)) {
+            let height = self.cells.len() / self.width;
+            let neighbours = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            for (nx, ny) in neighbours {
+                if nx < self.width && ny < height {
+                    edge((nx, ny), self.cells[self.index((nx, ny))]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn astar_agrees_with_dijkstra() {
+        let width = 9;
+        let cells = (0..width * 7).map(|i| (i * 7 + i / 3) % 9 + 1).collect();
+        let graph = Costs { width, cells };
+        let goal = (8, 6);
+
+        let expected = dijkstra(&graph, [(0, 0)], |node| node == goal);
+        let (_, cost) = expected.goal().unwrap();
+
+        let blind = astar(&graph, [(0, 0)], |node| node == goal, |_| 0);
+        assert_eq!(blind.goal(), Some((goal, cost)));
+        assert_eq!(blind.path_to(goal), expected.path_to(goal));
+
+        // Every step costs at least one, so the Manhattan distance never overshoots.
+        let manhattan = |(x, y): (usize, usize)| goal.0 - x + goal.1 - y;
+        let guided = astar(&graph, [(0, 0)], |node| node == goal, manhattan);
+        assert_eq!(guided.goal(), Some((goal, cost)));
+        let path = guided.path_to(goal).unwrap();
+        let path_cost: usize = path[1..].iter().map(|&node| graph.cells[graph.index(node)]).sum();
+        assert_eq!(path_cost, cost);
+        assert!(guided.settled().len() <= expected.settled().len());
+    }
+}
@@ -0,0 +1,177 @@
+//! Python bindings for every day, so prototypes can check their answers against the Rust solutions, or lean on them
+//! for the parts that are too slow in Python. Build them into the current virtualenv with
+//! `maturin develop --release -m python/Cargo.toml` (or `tasks.py build-python`), then:
+//!
+//! ```python
+//! from aoc import day12
+//!
+//! day12.part1(day12.INPUT)
+//! ```
+//!
+//! Every day's module has `parse`, `part1`, `part2` and `solve` functions, which take the puzzle input as a string and
+//! raise a `ValueError` if it's malformed, along with the embedded puzzle input as `INPUT`. The parts and `solve` also
+//! raise one if the input breaks any of the assumptions the solution makes about puzzle inputs, rather than panicking.
+
+use aoc_common::{InputError, Solution};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+fn value_error(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+// The GIL is let go of while solving, so other Python threads can carry on in the meantime.
+
+fn parse<S: Solution>(py: Python<'_>, input: &str) -> PyResult<()> {
+    py.allow_threads(|| S::parse(input).map(drop)).map_err(value_error)
+}
+
+/// Parse `input` and make sure the parts can cope with it, since they're free to panic if not.
+fn checked<S: Solution>(input: &str) -> Result<S::Input<'_>, InputError> {
+    let input = S::parse(input)?;
+    S::check_assumptions(&input)?;
+    Ok(input)
+}
+
+fn part1<S: Solution>(py: Python<'_>, input: &str) -> PyResult<S::Part1>
+where
+    S::Part1: Send,
+{
+    py.allow_threads(|| checked::<S>(input).and_then(|input| Ok(S::part1(&input)?)))
+        .map_err(value_error)
+}
+
+fn part2<S: Solution>(py: Python<'_>, input: &str) -> PyResult<S::Part2>
+where
+    S::Part2: Send,
+{
+    py.allow_threads(|| checked::<S>(input).and_then(|input| Ok(S::part2(&input)?)))
+        .map_err(value_error)
+}
+
+fn solve<S: Solution>(py: Python<'_>, input: &str) -> PyResult<(S::Part1, S::Part2)>
+where
+    S::Part1: Send,
+    S::Part2: Send,
+{
+    py.allow_threads(|| S::solve(input)).map_err(value_error)
+}
+
+macro_rules! days {
+    ($($day:ident),+$(,)?) => {
+        $(
+            mod $day {
+                use ::$day::Puzzle;
+                use aoc_common::Solution;
+                use pyo3::prelude::*;
+
+                /// Check that `input` can be parsed, raising a `ValueError` saying where it went wrong if not.
+                #[pyfunction]
+                fn parse(py: Python<'_>, input: &str) -> PyResult<()> {
+                    super::parse::<Puzzle>(py, input)
+                }
+
+                /// Parse `input` and solve part 1, raising a `ValueError` if `input` breaks any of the assumptions the
+                /// solution makes about the puzzle inputs.
+                #[pyfunction]
+                fn part1(py: Python<'_>, input: &str) -> PyResult<<Puzzle as Solution>::Part1> {
+                    super::part1::<Puzzle>(py, input)
+                }
+
+                /// Parse `input` and solve part 2, raising a `ValueError` if `input` breaks any of the assumptions the
+                /// solution makes about the puzzle inputs.
+                #[pyfunction]
+                fn part2(py: Python<'_>, input: &str) -> PyResult<<Puzzle as Solution>::Part2> {
+                    super::part2::<Puzzle>(py, input)
+                }
+
+                /// Solve both parts, like the Rust solution does: raising a `ValueError` if `input` breaks any of the
+                /// assumptions it makes about the puzzle inputs, too.
+                #[pyfunction]
+                fn solve(
+                    py: Python<'_>,
+                    input: &str,
+                ) -> PyResult<(<Puzzle as Solution>::Part1, <Puzzle as Solution>::Part2)> {
+                    super::solve::<Puzzle>(py, input)
+                }
+
+                pub fn module(py: Python<'_>) -> PyResult<Bound<'_, PyModule>> {
+                    let module = PyModule::new(py, stringify!($day))?;
+                    module.add_function(wrap_pyfunction!(parse, &module)?)?;
+                    module.add_function(wrap_pyfunction!(part1, &module)?)?;
+                    module.add_function(wrap_pyfunction!(part2, &module)?)?;
+                    module.add_function(wrap_pyfunction!(solve, &module)?)?;
+                    module.add("INPUT", Puzzle::INPUT)?;
+                    Ok(module)
+                }
+            }
+        )+
+
+        #[pymodule]
+        fn aoc(module: &Bound<'_, PyModule>) -> PyResult<()> {
+            $(module.add_submodule(&$day::module(module.py())?)?;)+
+            Ok(())
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{ffi::c_str, types::PyDict};
+
+    use super::*;
+
+    #[test]
+    fn solves_from_python() {
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "aoc").unwrap();
+            aoc(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("aoc", module).unwrap();
+            let run = |code| py.run(code, Some(&globals), None);
+
+            run(c_str!("assert aoc.day01.solve(aoc.day01.INPUT) == (54916, 54728)")).unwrap();
+            run(c_str!("assert aoc.day25.part2(aoc.day25.INPUT) == 'Merry Christmas!'")).unwrap();
+            run(c_str!(
+                "assert aoc.day06.part2('Time: 71530\\nDistance: 940200') == 71503"
+            ))
+            .unwrap();
+
+            let err = run(c_str!("aoc.day06.parse('Time: 7')")).err();
+            assert!(err.is_some_and(|err| err.is_instance_of::<PyValueError>(py)));
+
+            // A pattern with no reflection, which part 1 would otherwise panic over.
+            let err = run(c_str!("aoc.day13.part1('#.\\n.#')")).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert!(err.to_string().contains("unsupported input"), "{err}");
+        });
+    }
+}
+
+#[rustfmt::skip]
+days!(
+    day01,
+    day02,
+    day03,
+    day04,
+    day05,
+    day06,
+    day07,
+    day08,
+    day09,
+    day10,
+    day11,
+    day12,
+    day13,
+    day14,
+    day15,
+    day16,
+    day17,
+    day18,
+    day19,
+    day20,
+    day21,
+    day22,
+    day23,
+    day24,
+    day25,
+);
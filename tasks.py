@@ -3,10 +3,9 @@ import subprocess
 import sys
 import typing as t
 import webbrowser
-from uuid import uuid4
 from datetime import datetime
 from functools import partial, wraps
-from os import chdir, environ, startfile
+from os import chdir, environ
 from pathlib import Path
 
 import browser_cookie3
@@ -22,25 +21,79 @@ from termcolor import colored as c
 cb = partial(c, attrs=["bold"])
 
 MAIN = """\
-fn main() {{
-    let (part1, part2) = {crate}::solve();
+fn main() -> color_eyre::Result<()> {{
+    color_eyre::install()?;
+    let (part1, part2) = {crate}::solve()?;
+    aoc_common::bounds::warn_if_ruled_out("{crate}", &part1, &part2);
     println!("{{part1}}");
     println!("{{part2}}");
+    Ok(())
 }}\
 """
 
 LIB = """\
 use std::fmt::Display;
 
+use aoc_common::{InputError, ParseError, Solution};
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const INPUT: &'static str = include_str!("input.txt");
+
+    type Input<'a> = &'a str;
+    type Part1 = &'static str;
+    type Part2 = &'static str;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        Ok(input)
+    }
+
+    fn part1(_input: &Self::Input<'_>) -> Self::Part1 {
+        "TODO"
+    }
+
+    fn part2(_input: &Self::Input<'_>) -> Self::Part2 {
+        "TODO"
+    }
+}
+
+#[inline]
+pub fn solve() -> Result<(impl Display, impl Display), InputError> {
+    solve_input(Puzzle::INPUT)
+}
+
 #[inline]
-pub fn solve() -> (impl Display, impl Display) {
-    ("TODO", "TODO")
+pub fn solve_input(input: &str) -> Result<(impl Display, impl Display), InputError> {
+    Puzzle::solve(input)
 }\
 """
 
+FUZZ_TARGET = """\
+#![no_main]
+
+use aoc_common::Solution as _;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {{
+    // Malformed input should be reported as an error, not panic.
+    let _ = {crate}::Puzzle::parse(input);
+}});
+"""
+
+FUZZ_BIN = """
+[[bin]]
+name = "parse_{crate}"
+path = "fuzz_targets/parse_{crate}.rs"
+test = false
+doc = false
+bench = false
+"""
+
 DEFAULT_BASELINE = "previous"
 
 WORKSPACE_MANIFEST_PATH = Path(__file__).parent / "Cargo.toml"
+ANSWER_BOUNDS_PATH = Path(__file__).parent / "answer_bounds.toml"
 
 NOW = datetime.now()
 
@@ -117,6 +170,8 @@ def start_solve(day: int = min(DAYS_LEFT, default=0)) -> None:
         toml.dump(manifest, manifest_f)
 
     run(("cargo", "new", "--bin", crate))
+    run(("cargo", "add", "--manifest-path", f"{crate}/Cargo.toml", "--path", "common", "aoc-common"))
+    run(("cargo", "add", "--manifest-path", f"{crate}/Cargo.toml", "color-eyre@0.6.2"))
     run(
         (
             "cargo",
@@ -129,14 +184,40 @@ def start_solve(day: int = min(DAYS_LEFT, default=0)) -> None:
         )
     )
 
+    run(
+        (
+            "cargo",
+            "add",
+            "--manifest-path",
+            "python/Cargo.toml",
+            "--path",
+            crate,
+            crate,
+        )
+    )
+
+    run(
+        (
+            "cargo",
+            "add",
+            "--manifest-path",
+            "fuzz/Cargo.toml",
+            "--path",
+            crate,
+            crate,
+        )
+    )
+    with Path("fuzz", "Cargo.toml").open("a", newline="\n") as fuzz_manifest_f:
+        fuzz_manifest_f.write(FUZZ_BIN.format(crate=crate))
+    Path("fuzz", "fuzz_targets", f"parse_{crate}.rs").write_text(FUZZ_TARGET.format(crate=crate), newline="\n")
+
     src = crate_path / "src"
     (src / "main.rs").write_text(MAIN.format(crate=crate), newline="\n")
     (src / "lib.rs").write_text(LIB, newline="\n")
     (src / "input.txt").write_text(puzzle_input, newline="\n")
 
-    benches = Path("benchmark", "benches")
-    add_line(benches / "criterion.rs", f"    {crate},")
-    add_line(benches / "iai.rs", f"    {crate}: {crate}_solve,")
+    add_line(Path("benchmark", "src", "lib.rs"), f"    {crate},")
+    add_line(Path("python", "src", "lib.rs"), f"    {crate},")
 
     fetch_problem(YEAR, day)
 
@@ -192,6 +273,47 @@ def compare_by_stashing(day: str, name: str = DEFAULT_BASELINE) -> None:
     compare(day, name)
 
 
+@in_root_dir
+@aliases("regressions")
+def check_regressions(name: str = DEFAULT_BASELINE, threshold: float = 5.0) -> None:
+    "Check the latest criterion run against a saved baseline, failing if any day got slower than the threshold."
+    run(
+        (
+            "cargo",
+            "run",
+            "--release",
+            "--bin",
+            "regressions",
+            "--",
+            "--baseline",
+            name,
+            "--threshold",
+            str(threshold),
+        )
+    )
+
+
+@in_root_dir
+@aliases("cmp-threads")
+def compare_threading(day: str = "all") -> None:
+    "Run a criterion benchmark with the parallel feature, then compare a sequential run against it."
+    set_baseline(day, "parallel")
+    run(
+        (
+            "cargo",
+            "bench",
+            "--no-default-features",
+            "--bench",
+            "criterion",
+            "--",
+            day,
+            "--baseline",
+            "parallel",
+            "--verbose",
+        )
+    )
+
+
 @in_root_dir
 def criterion(day: str) -> None:
     "Run a criterion benchmark, without caring about baselines."
@@ -204,6 +326,12 @@ def iai() -> None:
     run(("cargo", "bench", "--bench", "iai"))
 
 
+@in_root_dir
+def fuzz(day: str) -> None:
+    "Fuzz a day's parser, looking for inputs that make it panic."
+    run(("cargo", "+nightly", "fuzz", "run", f"parse_{day}"))
+
+
 @aliases("wr")
 def watch_run() -> None:
     "Run the solution everytime it changes."
@@ -231,6 +359,13 @@ def run_prototype() -> None:
     run(("cargo", "watch", "--clear", "--shell", "python3 prototype.py"))
 
 
+@aliases("bp")
+@in_root_dir
+def build_python() -> None:
+    "Build the Python bindings to the solutions into the current virtualenv, for prototypes to import."
+    run(("maturin", "develop", "--release", "--manifest-path", "python/Cargo.toml"))
+
+
 @arg("level", help="Which part to submit.", choices=(1, 2))
 @aliases("a")
 @wrap_errors((requests.HTTPError, AssertionError))
@@ -256,7 +391,33 @@ def answer(answer: str, level: int) -> None:
         return_link.decompose()
     h = html2text.HTML2Text()
     h.ignore_links = True
-    print(h.handle(str(soup)).strip())
+    text = h.handle(str(soup)).strip()
+    print(text)
+
+    # Remember the right answer, so that the answers test can catch us changing it.
+    if "That's the right answer" in text:
+        manifest = toml.parse(WORKSPACE_MANIFEST_PATH.read_text())
+        metadata = manifest["workspace"].setdefault("metadata", {})  # type: ignore
+        answers = metadata.setdefault(day, {}).setdefault("answers", ["", ""])
+        answers[level - 1] = answer
+
+        with WORKSPACE_MANIFEST_PATH.open("w") as manifest_f:
+            toml.dump(manifest, manifest_f)
+
+    # Remember wrong answers too, so that main.rs can warn us before we submit them again.
+    elif "That's not the right answer" in text:
+        if "too high" in text:
+            kind = "too_high"
+        elif "too low" in text:
+            kind = "too_low"
+        else:
+            kind = "wrong"
+        bounds = toml.parse(ANSWER_BOUNDS_PATH.read_text())
+        known = bounds.setdefault(day, {}).setdefault(f"part{level}", {}).setdefault(kind, [])
+        known.append(int(answer) if answer.lstrip("-").isdigit() else answer)
+
+        with ANSWER_BOUNDS_PATH.open("w") as bounds_f:
+            toml.dump(bounds, bounds_f)
 
 
 @in_root_dir
@@ -320,46 +481,13 @@ def set_completion_time() -> None:
         toml.dump(manifest, manifest_f)
 
 @in_root_dir
-def flamegraph(day: str, *, remote = "linode") -> None:
-    "Run a flamegraph benchmark on a remote."
-    import tarfile
-    import tempfile
-
-    from rich.console import Console
-
-    console = Console()
-
-    def filter(info):
-        if any(s in info.name for s in (".git", "target")):
-            console.log(f"{info.name!r} [red]skipped.[/red]")
-            return None
-        console.log(f"{info.name!r} [green]added to tarball.[/green]")
-        return info
-    
-    # Generate a zipped tarball of the current source code.
-    archive_stem = str(uuid4())
-    archive_name = f"{archive_stem}.tar.gz"
-    with tempfile.TemporaryDirectory() as tmpdir:
-        archive_path = Path(tmpdir, archive_name)
-        with console.status("Compressing..."), tarfile.open(archive_path, "w:gz") as tar:
-            tar.add(".", filter=filter)
-
-        # Upload it to the remote via scp and untar it.
-        run(("scp", "-C", archive_path, f"{remote}:/tmp/{archive_name}"))
-        run(("ssh", remote, "tar", "-xzf", f"/tmp/{archive_name}", "--one-top-level", "-C", "/tmp"))
-
-    # Run the benchmark on the remote.
-    run(("ssh", remote, "cd", f"/tmp/{archive_stem}", "&&", 
-        "CARGO_PROFILE_BENCH_DEBUG=true", "cargo", "flamegraph", "--bench", "criterion", "--", "--bench", day))
-
-    # Download the flamegraph.
-    run(("scp", f"{remote}:/tmp/{archive_stem}/flamegraph.svg", "."))
-
-    # Remove the archive from the remote.
-    run(("ssh", remote, "rm", "-rf", f"/tmp/{archive_stem}", f"/tmp/{archive_name}"))
-
-    # Open the flamegraph.
-    startfile("flamegraph.svg")
+def flamegraph(day: str, seconds: int = 10) -> None:
+    "Profile a criterion benchmark locally, then open the flamegraph it writes under target/criterion."
+    environ["CARGO_PROFILE_BENCH_DEBUG"] = "true"
+    run(("cargo", "bench", "--bench", "criterion", "--", "--profile-time", str(seconds), f"^{day}$"))
+    svg = Path("target", "criterion", *day.split("/"), "profile", "flamegraph.svg")
+    webbrowser.open_new(svg.resolve().as_uri())
+
 
 def main() -> None:
     # environ["RUST_BACKTRACE"] = "1"
@@ -371,12 +499,16 @@ def main() -> None:
             set_baseline,
             compare,
             compare_by_stashing,
+            compare_threading,
+            check_regressions,
             criterion,
             iai,
+            fuzz,
             watch_run,
             do_run,
             run_release,
             run_prototype,
+            build_python,
             update_pipreqs,
             show_session_cookie,
             measure_completion_time,